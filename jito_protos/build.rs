@@ -12,6 +12,8 @@ fn main() {
                 "protos/relayer.proto",
                 "protos/searcher.proto",
                 "protos/shared.proto",
+                "protos/shredstream.proto",
+                "protos/trace_shred.proto",
             ],
             &["protos"],
         )
@@ -0,0 +1,136 @@
+use std::str::FromStr;
+
+use log::{error, info};
+use ore::{BUS_ADDRESSES, MINT_ADDRESS, TREASURY_ADDRESS};
+use solana_program::{
+    address_lookup_table::{
+        instruction::{create_lookup_table, extend_lookup_table},
+        state::AddressLookupTable,
+    },
+    instruction::Instruction,
+    pubkey::Pubkey,
+    sysvar,
+};
+use solana_sdk::{
+    address_lookup_table_account::AddressLookupTableAccount, commitment_config::CommitmentConfig,
+    signature::Signer,
+};
+
+use crate::{
+    chain::Chain,
+    send_and_confirm::JITO_TIP_ACCOUNT,
+    utils::{get_account_data, proof_pubkey, treasury_tokens_pubkey},
+    Miner,
+};
+
+// Addresses per extend instruction, small enough to fit in one transaction
+const MAX_EXTEND_ADDRESSES: usize = 20;
+
+impl Miner {
+    pub async fn alt_create(&self) {
+        let feepayer = self.feepayer();
+
+        // The table address is derived from a recent finalized slot
+        let recent_slot = match self.chain.get_slot(CommitmentConfig::finalized()).await
+        {
+            Ok(slot) => slot,
+            Err(err) => {
+                error!("Error fetching recent slot: {}", err);
+                return;
+            }
+        };
+        let (create_ix, address) =
+            create_lookup_table(feepayer.pubkey(), feepayer.pubkey(), recent_slot);
+        let mut ixs = vec![create_ix];
+        ixs.extend(self.extend_ixs(address, &self.lookup_table_addresses()));
+
+        info!("Creating lookup table {}...", address);
+        match self.send_and_confirm(&ixs, &[]).await {
+            Ok(uuids) => {
+                info!(bundle = uuids.join(","); "Lookup table bundle sent");
+                info!("Pass --lookup-table {} to use it once it lands", address);
+            }
+            Err(err) => error!("Error creating lookup table: {}", err),
+        }
+    }
+
+    pub async fn alt_extend(&self, address: String) {
+        let Ok(address) = Pubkey::from_str(&address) else {
+            error!("Invalid address: {:?}", address);
+            return;
+        };
+        let table = match get_lookup_table(self.chain.as_ref(), address).await {
+            Ok(table) => table,
+            Err(err) => {
+                error!("Error fetching lookup table: {}", err);
+                return;
+            }
+        };
+
+        // Only add the addresses the table is missing, e.g. proofs of new wallets
+        let missing: Vec<Pubkey> = self
+            .lookup_table_addresses()
+            .into_iter()
+            .filter(|pubkey| !table.addresses.contains(pubkey))
+            .collect();
+        if missing.is_empty() {
+            info!("Lookup table {} is up to date, returning.", address);
+            return;
+        }
+
+        info!("Adding {} addresses to lookup table {}...", missing.len(), address);
+        match self.send_and_confirm(&self.extend_ixs(address, &missing), &[]).await {
+            Ok(uuids) => info!(bundle = uuids.join(","); "Lookup table bundle sent"),
+            Err(err) => error!("Error extending lookup table: {}", err),
+        }
+    }
+
+    /// The static Ore accounts and every miner's proof, for mine, reset and claim transactions.
+    fn lookup_table_addresses(&self) -> Vec<Pubkey> {
+        let mut addresses = BUS_ADDRESSES.to_vec();
+        addresses.extend([
+            TREASURY_ADDRESS,
+            treasury_tokens_pubkey(),
+            MINT_ADDRESS,
+            sysvar::slot_hashes::ID,
+            spl_token::id(),
+            JITO_TIP_ACCOUNT,
+        ]);
+        addresses.extend(
+            self.signers()
+                .iter()
+                .map(|signer| proof_pubkey(signer.pubkey())),
+        );
+        addresses
+    }
+
+    fn extend_ixs(&self, address: Pubkey, addresses: &[Pubkey]) -> Vec<Instruction> {
+        let feepayer = self.feepayer().pubkey();
+        addresses
+            .chunks(MAX_EXTEND_ADDRESSES)
+            .map(|chunk| extend_lookup_table(address, feepayer, Some(feepayer), chunk.to_vec()))
+            .collect()
+    }
+
+    /// The lookup table passed with --lookup-table, if any, for compiling transactions.
+    pub async fn get_lookup_tables(
+        &self,
+    ) -> Result<Vec<AddressLookupTableAccount>, Box<dyn std::error::Error>> {
+        match self.lookup_table {
+            Some(address) => Ok(vec![get_lookup_table(self.chain.as_ref(), address).await?]),
+            None => Ok(vec![]),
+        }
+    }
+}
+
+async fn get_lookup_table(
+    chain: &dyn Chain,
+    address: Pubkey,
+) -> Result<AddressLookupTableAccount, Box<dyn std::error::Error>> {
+    let data = get_account_data(chain, &address).await?;
+    let table = AddressLookupTable::deserialize(&data)?;
+    Ok(AddressLookupTableAccount {
+        key: address,
+        addresses: table.addresses.to_vec(),
+    })
+}
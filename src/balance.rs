@@ -1,41 +1,70 @@
 use std::str::FromStr;
 
-use solana_client::nonblocking::rpc_client::RpcClient;
+use serde::Serialize;
 use solana_program::pubkey::Pubkey;
-use solana_sdk::{commitment_config::CommitmentConfig, signature::Signer};
+use solana_sdk::signature::Signer;
 
-use crate::Miner;
+use crate::{
+    output::{self, OreAmount, OutputFormat, QueryOutput},
+    utils::get_token_account,
+    Miner,
+};
+
+/// The ORE held in a wallet's associated token account.
+#[derive(Serialize)]
+pub struct BalanceOutput {
+    pub address: String,
+    pub token_account: String,
+    /// ORE in the token account, `None` if it does not exist.
+    pub balance: Option<OreAmount>,
+}
+
+impl QueryOutput for BalanceOutput {
+    fn text(&self) -> Vec<String> {
+        match &self.balance {
+            Some(balance) => vec![format!("{} ORE", balance)],
+            None => vec!["Account not found".to_string()],
+        }
+    }
+
+    fn table(&self) -> (Vec<&'static str>, Vec<Vec<String>>) {
+        output::fields(&[
+            ("Address", self.address.clone()),
+            ("Token account", self.token_account.clone()),
+            (
+                "Balance",
+                self.balance
+                    .as_ref()
+                    .map_or("not found".to_string(), |balance| format!("{} ORE", balance)),
+            ),
+        ])
+    }
+}
 
 impl Miner {
-    pub async fn balance(&self, address: Option<String>) {
+    pub async fn balance(&self, address: Option<String>, format: OutputFormat) {
         let signer = &self.signers()[0];
         let address = if let Some(address) = address {
-            if let Ok(address) = Pubkey::from_str(&address) {
-                address
-            } else {
-                println!("Invalid address: {:?}", address);
-                return;
-            }
+            Pubkey::from_str(&address)
+                .unwrap_or_else(|_| output::fail(format!("Invalid address `{}`", address)))
         } else {
             signer.pubkey()
         };
-        let client =
-            RpcClient::new_with_commitment(self.cluster.clone(), CommitmentConfig::processed());
         let token_account_address = spl_associated_token_account::get_associated_token_address(
             &address,
             &ore::MINT_ADDRESS,
         );
-        match client.get_token_account(&token_account_address).await {
+        match get_token_account(self.chain.as_ref(), &token_account_address).await {
             Ok(token_account) => {
-                if let Some(token_account) = token_account {
-                    println!("{:} ORE", token_account.token_amount.ui_amount_string);
-                } else {
-                    println!("Account not found");
-                }
-            }
-            Err(err) => {
-                println!("{:?}", err);
+                let balance = BalanceOutput {
+                    address: address.to_string(),
+                    token_account: token_account_address.to_string(),
+                    balance: token_account
+                        .map(|token_account| OreAmount::new(token_account.amount)),
+                };
+                output::print(&balance, format);
             }
+            Err(err) => output::fail(format!("Could not fetch the token account: {}", err)),
         }
     }
 }
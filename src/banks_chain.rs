@@ -0,0 +1,110 @@
+//! The chain of a program-test bank, for running whole commands against the Ore program offline.
+
+use async_trait::async_trait;
+use solana_client::client_error::Result as ClientResult;
+use solana_program::{hash::Hash, pubkey::Pubkey};
+use solana_program_test::{BanksClient, BanksClientError};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+    transport::TransportError,
+};
+
+use crate::chain::Chain;
+
+/// Reads accounts from a program-test bank and processes transactions in it as they are sent.
+pub struct BanksChain {
+    banks: BanksClient,
+}
+
+impl BanksChain {
+    pub fn new(banks: BanksClient) -> Self {
+        Self { banks }
+    }
+
+    /// A handle to the bank, whose requests each need their own mutable client.
+    fn banks(&self) -> BanksClient {
+        self.banks.clone()
+    }
+}
+
+fn client_error(err: BanksClientError) -> solana_client::client_error::ClientError {
+    TransportError::from(err).into()
+}
+
+#[async_trait]
+impl Chain for BanksChain {
+    async fn get_account(&self, address: &Pubkey) -> ClientResult<Option<Account>> {
+        self.banks().get_account(*address).await.map_err(client_error)
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<Option<Account>>> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            accounts.push(self.get_account(address).await?);
+        }
+        Ok(accounts)
+    }
+
+    async fn get_multiple_accounts_with_commitment(
+        &self,
+        addresses: &[Pubkey],
+        _commitment: CommitmentConfig,
+    ) -> ClientResult<Vec<Option<Account>>> {
+        self.get_multiple_accounts(addresses).await
+    }
+
+    async fn get_slot(&self, _commitment: CommitmentConfig) -> ClientResult<u64> {
+        self.banks().get_root_slot().await.map_err(client_error)
+    }
+
+    async fn get_balance(&self, address: &Pubkey) -> ClientResult<u64> {
+        self.banks().get_balance(*address).await.map_err(client_error)
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        let rent = self.banks().get_rent().await.map_err(client_error)?;
+        Ok(rent.minimum_balance(data_len))
+    }
+
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        self.banks().get_latest_blockhash().await.map_err(client_error)
+    }
+
+    async fn send_transaction(&self, tx: &VersionedTransaction) -> ClientResult<Signature> {
+        self.banks()
+            .process_transaction(tx.clone())
+            .await
+            .map_err(client_error)?;
+        Ok(tx.signatures[0])
+    }
+
+    async fn simulate_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> ClientResult<Option<TransactionError>> {
+        let simulation = self
+            .banks()
+            .simulate_transaction(tx.clone())
+            .await
+            .map_err(client_error)?;
+        Ok(simulation.result.and_then(Result::err))
+    }
+
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<Result<(), TransactionError>>> {
+        let status = self
+            .banks()
+            .get_transaction_status(*signature)
+            .await
+            .map_err(client_error)?;
+        Ok(status.map(|status| status.err.map_or(Ok(()), Err)))
+    }
+}
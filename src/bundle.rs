@@ -0,0 +1,164 @@
+use std::time::{Duration, Instant};
+
+use jito_protos::searcher::GetTipAccountsRequest;
+use tokio::time::sleep;
+
+use crate::{
+    jito_http::{JitoHttpClient, JitoHttpResult, MAX_BUNDLE_STATUS_IDS},
+    send_and_confirm::{BundleSender, BundleTransport},
+    Miner,
+};
+
+// Seconds between status polls of a pending bundle
+const STATUS_POLL_INTERVAL: u64 = 2;
+
+// Path of the JSON-RPC bundle API on a block engine host
+const BUNDLE_API_PATH: &str = "/api/v1/bundles";
+
+/// Where a sent bundle stands, as reported by the block engine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BundleStatus {
+    /// Not landed yet, or not seen by the block engine yet.
+    Pending,
+    /// Landed on chain in `slot`, with the signatures of its transactions.
+    Landed { slot: u64, signatures: Vec<String> },
+    /// Failed all auctions it was part of, or was invalid.
+    Failed,
+    /// Still pending when the poll gave up.
+    TimedOut,
+}
+
+impl Miner {
+    pub async fn tip_accounts(&self) {
+        let bundle_sender = match self.get_bundle_sender().await {
+            Ok(bundle_sender) => bundle_sender,
+            Err(err) => {
+                println!("Error connecting to block engine: {}", err);
+                return;
+            }
+        };
+        let accounts = match bundle_sender {
+            BundleSender::Grpc(mut searcher_client) => searcher_client
+                .get_tip_accounts(GetTipAccountsRequest {})
+                .await
+                .map(|response| response.into_inner().accounts)
+                .map_err(|err| err.to_string()),
+            BundleSender::Http(http_client) => http_client
+                .get_tip_accounts()
+                .await
+                .map_err(|err| err.to_string()),
+        };
+        match accounts {
+            Ok(accounts) => {
+                for account in accounts {
+                    println!("{}", account);
+                }
+            }
+            Err(err) => println!("Error fetching tip accounts: {}", err),
+        }
+    }
+
+    pub async fn bundle_status(&self, uuid: String, wait: Option<u64>) {
+        let status = match wait {
+            Some(seconds) => {
+                println!("Waiting up to {}s for bundle {}...", seconds, uuid);
+                self.poll_bundle_status(&uuid, Duration::from_secs(seconds))
+                    .await
+            }
+            None => self
+                .get_bundle_statuses(&[uuid.clone()])
+                .await
+                .map(|mut statuses| statuses.remove(0)),
+        };
+        match status {
+            Ok(BundleStatus::Pending) => println!("Bundle {} is pending", uuid),
+            Ok(BundleStatus::Landed { slot, signatures }) => {
+                println!("Bundle {} landed in slot {}", uuid, slot);
+                for signature in signatures {
+                    println!("  {}", signature);
+                }
+            }
+            Ok(BundleStatus::Failed) => println!("Bundle {} failed to land", uuid),
+            Ok(BundleStatus::TimedOut) => println!("Bundle {} has not landed yet", uuid),
+            Err(err) => println!("Error fetching bundle status: {}", err),
+        }
+    }
+
+    /// Polls the status of a bundle until it lands, fails or `timeout` passes.
+    pub async fn poll_bundle_status(
+        &self,
+        uuid: &str,
+        timeout: Duration,
+    ) -> JitoHttpResult<BundleStatus> {
+        let started_at = Instant::now();
+        loop {
+            let status = self
+                .get_bundle_statuses(&[uuid.to_string()])
+                .await?
+                .remove(0);
+            if status.ne(&BundleStatus::Pending) {
+                return Ok(status);
+            }
+            if started_at.elapsed().ge(&timeout) {
+                return Ok(BundleStatus::TimedOut);
+            }
+            sleep(Duration::from_secs(STATUS_POLL_INTERVAL)).await;
+        }
+    }
+
+    /// Fetches the statuses of bundles sent in the last five minutes, in order.
+    pub async fn get_bundle_statuses(&self, uuids: &[String]) -> JitoHttpResult<Vec<BundleStatus>> {
+        let client = self.bundle_status_client();
+        let mut statuses = Vec::with_capacity(uuids.len());
+        for chunk in uuids.chunks(MAX_BUNDLE_STATUS_IDS) {
+            let inflight = client.get_inflight_bundle_statuses(chunk).await?;
+
+            // Landed slots and signatures come from the landed bundle statuses
+            let landed: Vec<String> = inflight
+                .iter()
+                .flatten()
+                .filter(|status| status.status.eq("Landed"))
+                .map(|status| status.bundle_id.clone())
+                .collect();
+            let landed = match landed.is_empty() {
+                true => vec![],
+                false => client.get_bundle_statuses(&landed).await?,
+            };
+
+            for uuid in chunk {
+                let status = inflight
+                    .iter()
+                    .flatten()
+                    .find(|status| status.bundle_id.eq(uuid));
+                statuses.push(match status.map(|status| status.status.as_str()) {
+                    Some("Landed") => {
+                        let landed = landed.iter().flatten().find(|b| b.bundle_id.eq(uuid));
+                        BundleStatus::Landed {
+                            slot: landed
+                                .map(|b| b.slot)
+                                .or(status.and_then(|s| s.landed_slot))
+                                .unwrap_or_default(),
+                            signatures: landed.map(|b| b.transactions.clone()).unwrap_or_default(),
+                        }
+                    }
+                    Some("Failed") => BundleStatus::Failed,
+                    // Invalid bundles are unknown to the block engine, which includes
+                    // bundles it has not seen yet, so they stay pending until timeout
+                    _ => BundleStatus::Pending,
+                });
+            }
+        }
+        Ok(statuses)
+    }
+
+    /// The JSON-RPC client for bundle statuses, which the gRPC transport does not expose.
+    fn bundle_status_client(&self) -> JitoHttpClient {
+        let url = match self.bundle_transport {
+            BundleTransport::Http => self.be_url.clone(),
+            BundleTransport::Grpc => {
+                format!("{}{}", self.be_url.trim_end_matches('/'), BUNDLE_API_PATH)
+            }
+        };
+        JitoHttpClient::new(url)
+    }
+}
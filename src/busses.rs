@@ -1,28 +1,67 @@
 use ore::{state::Bus, utils::AccountDeserialize, BUS_ADDRESSES};
-use solana_client::{client_error::Result, nonblocking::rpc_client::RpcClient};
-use solana_sdk::commitment_config::CommitmentConfig;
+use serde::Serialize;
+use solana_client::client_error::Result;
 
-use crate::Miner;
+use crate::{
+    output::{self, OreAmount, OutputFormat, QueryOutput},
+    utils::get_account_data,
+    Miner,
+};
+
+/// The rewards left to distribute in one bus.
+#[derive(Serialize)]
+pub struct BusOutput {
+    pub id: u64,
+    pub address: String,
+    /// ORE the bus can still pay out this epoch.
+    pub rewards: OreAmount,
+}
+
+#[derive(Serialize)]
+pub struct BussesOutput {
+    pub busses: Vec<BusOutput>,
+}
+
+impl QueryOutput for BussesOutput {
+    fn text(&self) -> Vec<String> {
+        self.busses
+            .iter()
+            .map(|bus| format!("Bus {}: {} ORE", bus.id, bus.rewards))
+            .collect()
+    }
+
+    fn table(&self) -> (Vec<&'static str>, Vec<Vec<String>>) {
+        let rows = self
+            .busses
+            .iter()
+            .map(|bus| vec![bus.id.to_string(), bus.address.clone(), bus.rewards.to_string()])
+            .collect();
+        (vec!["BUS", "ADDRESS", "REWARDS ORE"], rows)
+    }
+}
 
 impl Miner {
-    pub async fn busses(&self) {
-        let client =
-            RpcClient::new_with_commitment(self.cluster.clone(), CommitmentConfig::processed());
+    pub async fn busses(&self, format: OutputFormat) {
+        let mut busses = vec![];
         for address in BUS_ADDRESSES.iter() {
-            let data = client.get_account_data(address).await.unwrap();
-            match Bus::try_from_bytes(&data) {
-                Ok(bus) => {
-                    println!("Bus {}: {:} ORE", bus.id, bus.rewards);
-                }
-                Err(_) => {}
+            let data = get_account_data(self.chain.as_ref(), address)
+                .await
+                .unwrap_or_else(|err| {
+                    output::fail(format!("Could not fetch {}: {}", address, err))
+                });
+            if let Ok(bus) = Bus::try_from_bytes(&data) {
+                busses.push(BusOutput {
+                    id: bus.id,
+                    address: address.to_string(),
+                    rewards: OreAmount::new(bus.rewards),
+                });
             }
         }
+        output::print(&BussesOutput { busses }, format);
     }
 
     pub async fn get_bus(&self, id: usize) -> Result<Bus> {
-        let client =
-            RpcClient::new_with_commitment(self.cluster.clone(), CommitmentConfig::processed());
-        let data = client.get_account_data(&BUS_ADDRESSES[id]).await?;
+        let data = get_account_data(self.chain.as_ref(), &BUS_ADDRESSES[id]).await?;
         Ok(*Bus::try_from_bytes(&data).unwrap())
     }
 }
@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use solana_client::{
+    client_error::Result as ClientResult, nonblocking::rpc_client::RpcClient,
+    rpc_config::RpcSimulateTransactionConfig,
+};
+use solana_program::{hash::Hash, pubkey::Pubkey};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+};
+
+/// The chain reads and writes commands need, so they can run against a fake in tests.
+#[async_trait]
+pub trait Chain: Send + Sync {
+    /// The account at `address`, `None` if it does not exist.
+    async fn get_account(&self, address: &Pubkey) -> ClientResult<Option<Account>>;
+
+    /// The accounts at up to 100 addresses, in order.
+    async fn get_multiple_accounts(&self, addresses: &[Pubkey])
+        -> ClientResult<Vec<Option<Account>>>;
+
+    /// The accounts at up to 100 addresses, in order, as of `commitment`.
+    async fn get_multiple_accounts_with_commitment(
+        &self,
+        addresses: &[Pubkey],
+        commitment: CommitmentConfig,
+    ) -> ClientResult<Vec<Option<Account>>>;
+
+    /// The highest slot that has reached `commitment`.
+    async fn get_slot(&self, commitment: CommitmentConfig) -> ClientResult<u64>;
+
+    async fn get_balance(&self, address: &Pubkey) -> ClientResult<u64>;
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64>;
+
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash>;
+
+    async fn send_transaction(&self, tx: &VersionedTransaction) -> ClientResult<Signature>;
+
+    /// Simulates a transaction without verifying signatures, returning its error if it fails.
+    async fn simulate_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> ClientResult<Option<TransactionError>>;
+
+    /// The result of a transaction, `None` if it has not been processed.
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<Result<(), TransactionError>>>;
+}
+
+/// The chain as seen through an RPC node at processed commitment.
+pub struct RpcChain {
+    client: RpcClient,
+}
+
+impl RpcChain {
+    pub fn new(cluster: String) -> Self {
+        Self {
+            client: RpcClient::new_with_commitment(cluster, CommitmentConfig::processed()),
+        }
+    }
+}
+
+#[async_trait]
+impl Chain for RpcChain {
+    async fn get_account(&self, address: &Pubkey) -> ClientResult<Option<Account>> {
+        Ok(self
+            .client
+            .get_account_with_commitment(address, self.client.commitment())
+            .await?
+            .value)
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<Option<Account>>> {
+        self.client.get_multiple_accounts(addresses).await
+    }
+
+    async fn get_multiple_accounts_with_commitment(
+        &self,
+        addresses: &[Pubkey],
+        commitment: CommitmentConfig,
+    ) -> ClientResult<Vec<Option<Account>>> {
+        Ok(self
+            .client
+            .get_multiple_accounts_with_commitment(addresses, commitment)
+            .await?
+            .value)
+    }
+
+    async fn get_slot(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        self.client.get_slot_with_commitment(commitment).await
+    }
+
+    async fn get_balance(&self, address: &Pubkey) -> ClientResult<u64> {
+        self.client.get_balance(address).await
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        self.client
+            .get_minimum_balance_for_rent_exemption(data_len)
+            .await
+    }
+
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        let (hash, _slot) = self
+            .client
+            .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
+            .await?;
+        Ok(hash)
+    }
+
+    async fn send_transaction(&self, tx: &VersionedTransaction) -> ClientResult<Signature> {
+        self.client.send_transaction(tx).await
+    }
+
+    async fn simulate_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> ClientResult<Option<TransactionError>> {
+        let simulation = self
+            .client
+            .simulate_transaction_with_config(
+                tx,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    commitment: Some(CommitmentConfig::processed()),
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )
+            .await?;
+        Ok(simulation.value.err)
+    }
+
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<Result<(), TransactionError>>> {
+        self.client.get_signature_status(signature).await
+    }
+}
@@ -1,64 +1,153 @@
 use std::str::FromStr;
 
+use log::{error, info};
 use ore::{self, state::Proof, utils::AccountDeserialize};
-use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_program::pubkey::Pubkey;
-use solana_sdk::{
-    commitment_config::CommitmentConfig,
-    signature::Signer,
-};
+use solana_sdk::signature::{Keypair, Signer};
 
-use crate::{utils::proof_pubkey, Miner};
+use crate::{
+    utils::{get_multiple_accounts_batched, get_token_account, ore_ui_amount, proof_pubkey},
+    Miner,
+};
 
 impl Miner {
-    pub async fn claim(&self, cluster: String, beneficiary: Option<String>, amount: Option<f64>) {
-        let signer = &self.signers()[0];
-        let pubkey = signer.pubkey();
-        let client = RpcClient::new_with_commitment(cluster, CommitmentConfig::processed());
-        let beneficiary = match beneficiary {
-            Some(beneficiary) => {
-                Pubkey::from_str(&beneficiary).expect("Failed to parse beneficiary address")
+    pub async fn claim(
+        &self,
+        beneficiary: Option<String>,
+        amount: Option<f64>,
+        all: bool,
+        wallets: Vec<String>,
+    ) {
+        let signers = self.signers();
+        let claimers: Vec<&Keypair> = if all {
+            signers.iter().collect()
+        } else if !wallets.is_empty() {
+            let mut claimers = Vec::new();
+            for wallet in wallets {
+                let Ok(pubkey) = Pubkey::from_str(&wallet) else {
+                    error!("Invalid address: {:?}", wallet);
+                    return;
+                };
+                match signers.iter().find(|signer| signer.pubkey().eq(&pubkey)) {
+                    Some(signer) => claimers.push(signer),
+                    None => {
+                        error!("{} is not a miner wallet in keys.txt", pubkey);
+                        return;
+                    }
+                }
             }
-            None => self.initialize_ata().await,
-        };
-        let amount = if let Some(amount) = amount {
-            (amount * 10f64.powf(ore::TOKEN_DECIMALS as f64)) as u64
+            claimers
         } else {
-            match client.get_account(&proof_pubkey(pubkey)).await {
-                Ok(proof_account) => {
-                    let proof = Proof::try_from_bytes(&proof_account.data).unwrap();
-                    proof.claimable_rewards
-                }
-                Err(err) => {
-                    println!("Error looking up claimable rewards: {:?}", err);
+            vec![&signers[0]]
+        };
+        let beneficiary = match beneficiary {
+            Some(beneficiary) => match Pubkey::from_str(&beneficiary) {
+                Ok(beneficiary) => beneficiary,
+                Err(_) => {
+                    error!("Invalid beneficiary address: {:?}", beneficiary);
                     return;
                 }
-            }
+            },
+            None => self.initialize_ata().await,
         };
-        let amountf = (amount as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
-        //let cu_limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(CU_LIMIT_CLAIM);
-        //let cu_price_ix = ComputeBudgetInstruction::set_compute_unit_price(self.priority_fee);
-        let ix = ore::instruction::claim(pubkey, beneficiary, amount);
-        println!("Submitting claim transaction...");
-        match self
-            .send_and_confirm(&[ix], &[signer])
+        let requested = amount.map(|amount| (amount * 10f64.powf(ore::TOKEN_DECIMALS as f64)) as u64);
+        if let Err(err) = self.claim_rewards(&claimers, beneficiary, requested).await {
+            error!("Error claiming rewards: {}", err);
+        }
+    }
+
+    /// Sends a claim of each wallet's rewards to `beneficiary`, at most `requested` base units
+    /// each, skipping wallets that are not registered or have nothing to claim. Returns the
+    /// wallets and amounts claimed.
+    pub async fn claim_rewards(
+        &self,
+        claimers: &[&Keypair],
+        beneficiary: Pubkey,
+        requested: Option<u64>,
+    ) -> Result<Vec<(Pubkey, u64)>, Box<dyn std::error::Error>> {
+        // Fetch the proofs of every claiming wallet in batched requests
+        let proof_addresses: Vec<Pubkey> = claimers
+            .iter()
+            .map(|signer| proof_pubkey(signer.pubkey()))
+            .collect();
+        let proof_accounts = get_multiple_accounts_batched(self.chain.as_ref(), &proof_addresses)
             .await
-        {
-            Ok(sig) => {
-                println!("Claimed {:} ORE to account {:}", amountf, beneficiary);
-                println!("{:?}", sig);
+            .map_err(|err| format!("could not look up claimable rewards: {}", err))?;
+
+        // Build one claim instruction per wallet with rewards to claim
+        let mut ixs = Vec::new();
+        let mut ix_signers = Vec::new();
+        let mut claims = Vec::new();
+        for (signer, account) in claimers.iter().zip(proof_accounts) {
+            let pubkey = signer.pubkey();
+            let Some(account) = account else {
+                info!(wallet:% = pubkey; "Not registered, skipping");
+                continue;
+            };
+            let claimable = Proof::try_from_bytes(&account.data)
+                .map_err(|_| format!("could not parse the proof of {}", pubkey))?
+                .claimable_rewards;
+            let amount = requested.map_or(claimable, |requested| requested.min(claimable));
+            if amount.eq(&0) {
+                info!(wallet:% = pubkey; "No rewards to claim, skipping");
+                continue;
             }
-            Err(err) => {
-                println!("Error: {:?}", err);
+            ixs.push(ore::instruction::claim(pubkey, beneficiary, amount));
+            ix_signers.push(*signer);
+            claims.push((pubkey, amount));
+        }
+        if ixs.is_empty() {
+            info!("No rewards to claim, returning.");
+            return Ok(claims);
+        }
+
+        // A sent claim is only final once its bundle lands
+        info!("Submitting claim transactions for {} wallets...", ixs.len());
+        let ids = self.send_and_confirm(&ixs, &ix_signers).await?;
+        let mut total = 0;
+        for (pubkey, amount) in claims.iter() {
+            info!(wallet:% = pubkey; "Claim of {} ORE sent", ore_ui_amount(*amount));
+            total += amount;
+        }
+        info!(
+            bundle = ids.join(",");
+            "Sent claims of {} ORE to account {}",
+            ore_ui_amount(total),
+            beneficiary
+        );
+        Ok(claims)
+    }
+
+    pub async fn auto_claim(
+        &self,
+        signers: &[&Keypair],
+        proofs: &[Proof],
+        threshold: u64,
+        beneficiary: Pubkey,
+    ) {
+        // Claim the full balance of every wallet over the threshold
+        let mut ixs = Vec::new();
+        let mut ix_signers = Vec::new();
+        for (signer, proof) in signers.iter().zip(proofs) {
+            let claimable = proof.claimable_rewards;
+            if claimable.gt(&0) && claimable.ge(&threshold) {
+                let amountf = (claimable as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
+                info!(wallet:% = signer.pubkey(); "Auto-claiming {:} ORE", amountf);
+                ixs.push(ore::instruction::claim(signer.pubkey(), beneficiary, claimable));
+                ix_signers.push(*signer);
             }
         }
+        if ixs.is_empty() {
+            return;
+        }
+        match self.send_and_confirm(&ixs, &ix_signers).await {
+            Ok(uuids) => info!(bundle = uuids.join(","); "Auto-claim bundle sent"),
+            Err(err) => error!("Auto-claim error: {}", err),
+        }
     }
 
-    async fn initialize_ata(&self) -> Pubkey {
-        // Initialize client.
+    pub async fn initialize_ata(&self) -> Pubkey {
         let signer = &self.signers()[0];
-        let client =
-            RpcClient::new_with_commitment(self.cluster.clone(), CommitmentConfig::processed());
 
         // Build instructions.
         let token_account_pubkey = spl_associated_token_account::get_associated_token_address(
@@ -67,7 +156,7 @@ impl Miner {
         );
 
         // Check if ata already exists
-        if let Ok(Some(_ata)) = client.get_token_account(&token_account_pubkey).await {
+        if let Ok(Some(_ata)) = get_token_account(self.chain.as_ref(), &token_account_pubkey).await {
             return token_account_pubkey;
         }
 
@@ -78,13 +167,13 @@ impl Miner {
             &ore::MINT_ADDRESS,
             &spl_token::id(),
         );
-        println!("Creating token account {}...", token_account_pubkey);
+        info!("Creating token account {}...", token_account_pubkey);
         match self
             .send_and_confirm(&[ix], &[signer])
             .await
         {
-            Ok(_sig) => println!("Created token account {:?}", token_account_pubkey),
-            Err(e) => println!("Transaction failed: {:?}", e),
+            Ok(_sig) => info!("Created token account {}", token_account_pubkey),
+            Err(e) => error!("Error creating token account: {}", e),
         }
 
         // Return token account address
@@ -0,0 +1,177 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use jito_protos::{
+    convert::versioned_tx_from_packet,
+    searcher::{
+        mempool_subscription::Msg, MempoolSubscription, PendingTxNotification,
+        WriteLockedAccountSubscriptionV0,
+    },
+};
+use log::{info, warn};
+use ore::{instruction::OreInstruction, state::Bus, utils::AccountDeserialize, BUS_ADDRESSES, BUS_COUNT};
+use rand::seq::SliceRandom;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::VersionedTransaction,
+};
+use tokio::time::sleep;
+use tonic::Streaming;
+
+use crate::{
+    send_and_confirm::{connect_searcher, BlockEngineConnectionResult},
+    Miner,
+};
+
+// Seconds a pending mine transaction counts towards the contention of its bus
+const CONTENTION_WINDOW: u64 = 10;
+
+// Seconds to wait before resubscribing to a dropped mempool subscription, doubling on each
+// failed attempt up to the max
+const RESUBSCRIBE_DELAY: u64 = 1;
+const MAX_RESUBSCRIBE_DELAY: u64 = 60;
+
+/// Pending mine transactions per bus, seen in the block engine's mempool.
+#[derive(Clone)]
+pub struct BusContention {
+    pending: Arc<Mutex<Vec<VecDeque<Instant>>>>,
+}
+
+impl BusContention {
+    pub fn new() -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(vec![VecDeque::new(); BUS_COUNT])),
+        }
+    }
+
+    /// Counts a pending mine transaction seen on the bus.
+    pub fn record(&self, bus_id: usize) {
+        let mut pending = self.pending.lock().unwrap();
+        pending[bus_id].push_back(Instant::now());
+    }
+
+    /// Forgets every pending transaction, so buses are picked at random until more are seen.
+    pub fn clear(&self) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.iter_mut().for_each(VecDeque::clear);
+    }
+
+    /// Pending mine transactions per bus within the contention window.
+    pub fn counts(&self) -> Vec<usize> {
+        let window = Duration::from_secs(CONTENTION_WINDOW);
+        let mut pending = self.pending.lock().unwrap();
+        pending
+            .iter_mut()
+            .map(|seen| {
+                while seen.front().is_some_and(|at| at.elapsed().gt(&window)) {
+                    seen.pop_front();
+                }
+                seen.len()
+            })
+            .collect()
+    }
+}
+
+impl Miner {
+    /// Counts pending mine transactions per bus from a write-lock mempool subscription,
+    /// resubscribing with a backoff whenever it drops.
+    pub async fn subscribe_bus_contention(&self) -> BlockEngineConnectionResult<BusContention> {
+        let auth = Arc::new(self.auth());
+        let be_url = self.be_url.clone();
+        let mut stream = subscribe_mempool(&be_url, &auth).await?;
+
+        // Our own transactions should not count against a bus
+        let feepayer = self.feepayer().pubkey();
+        let contention = BusContention::new();
+        let recorder = contention.clone();
+        tokio::spawn(async move {
+            loop {
+                while let Ok(Some(notification)) = stream.message().await {
+                    for tx in notification.transactions.iter().filter_map(versioned_tx_from_packet) {
+                        if tx.message.static_account_keys().first().eq(&Some(&feepayer)) {
+                            continue;
+                        }
+                        for bus_id in mined_buses(&tx) {
+                            recorder.record(bus_id);
+                        }
+                    }
+                }
+
+                // Stale counts would steer mining away from buses that are no longer contended
+                recorder.clear();
+                let mut retry_delay = RESUBSCRIBE_DELAY;
+                warn!("Mempool subscription dropped, picking buses at random until it is back");
+                stream = loop {
+                    sleep(Duration::from_secs(retry_delay)).await;
+                    match subscribe_mempool(&be_url, &auth).await {
+                        Ok(stream) => break stream,
+                        Err(err) => {
+                            retry_delay = retry_delay.saturating_mul(2).min(MAX_RESUBSCRIBE_DELAY);
+                            warn!("Error resubscribing to the mempool, retrying in {}s: {}", retry_delay, err);
+                        }
+                    }
+                };
+                info!("Mempool subscription restored");
+            }
+        });
+        Ok(contention)
+    }
+
+    /// The least contended bus with enough rewards, if any.
+    pub async fn find_uncontended_bus(
+        &self,
+        reward_rate: u64,
+        contention: &BusContention,
+    ) -> Option<Bus> {
+        let accounts = self.chain.get_multiple_accounts(&BUS_ADDRESSES).await.ok()?;
+        let mut buses: Vec<Bus> = accounts
+            .iter()
+            .flatten()
+            .filter_map(|account| Bus::try_from_bytes(&account.data).ok().copied())
+            .filter(|bus| bus.rewards.gt(&reward_rate.saturating_mul(4)))
+            .collect();
+
+        // Shuffle first so equally contended buses are picked at random
+        buses.shuffle(&mut rand::thread_rng());
+        let counts = contention.counts();
+        buses
+            .into_iter()
+            .min_by_key(|bus| counts[bus.id as usize])
+    }
+}
+
+/// Subscribes to the mempool transactions that write-lock a bus.
+async fn subscribe_mempool(
+    be_url: &str,
+    auth: &Arc<Keypair>,
+) -> BlockEngineConnectionResult<Streaming<PendingTxNotification>> {
+    let mut searcher_client = connect_searcher(be_url, auth).await?;
+    Ok(searcher_client
+        .subscribe_mempool(MempoolSubscription {
+            msg: Some(Msg::WlaV0Sub(WriteLockedAccountSubscriptionV0 {
+                accounts: BUS_ADDRESSES.iter().map(|bus| bus.to_string()).collect(),
+            })),
+            regions: vec![],
+        })
+        .await?
+        .into_inner())
+}
+
+/// Ids of the buses a transaction sends mine instructions to.
+/// Buses loaded from a lookup table cannot be resolved and are ignored.
+fn mined_buses(tx: &VersionedTransaction) -> Vec<usize> {
+    let keys = tx.message.static_account_keys();
+    let key = |index: u8| keys.get(index as usize);
+    tx.message
+        .instructions()
+        .iter()
+        .filter(|ix| key(ix.program_id_index).eq(&Some(&ore::ID)))
+        .filter(|ix| ix.data.first().eq(&Some(&(OreInstruction::Mine as u8))))
+        .filter_map(|ix| ix.accounts.get(1).and_then(|index| key(*index)))
+        .filter_map(|bus: &Pubkey| BUS_ADDRESSES.iter().position(|address| address.eq(bus)))
+        .collect()
+}
@@ -1,3 +1,34 @@
+use ore::instruction::OreInstruction;
+use solana_program::{instruction::Instruction, system_program};
+use solana_sdk::compute_budget;
+
 pub const CU_LIMIT_CLAIM: u32 = 11_000;
 pub const CU_LIMIT_RESET: u32 = 12_200;
 pub const CU_LIMIT_MINE: u32 = 6_200;
+pub const CU_LIMIT_TRANSFER: u32 = 150;
+pub const CU_LIMIT_COMPUTE_BUDGET: u32 = 150;
+
+// Compute units the runtime allots an instruction without a compute budget instruction
+pub const CU_LIMIT_DEFAULT: u32 = 200_000;
+
+// Most compute units a single transaction may consume
+pub const CU_LIMIT_TRANSACTION: u32 = 1_400_000;
+
+/// Estimated compute units consumed by an instruction.
+pub fn estimate_compute_units(ix: &Instruction) -> u32 {
+    if ix.program_id.eq(&system_program::ID) {
+        return CU_LIMIT_TRANSFER;
+    }
+    if ix.program_id.eq(&compute_budget::ID) {
+        return CU_LIMIT_COMPUTE_BUDGET;
+    }
+    if ix.program_id.ne(&ore::ID) {
+        return CU_LIMIT_DEFAULT;
+    }
+    match ix.data.first().map(|tag| OreInstruction::try_from(*tag)) {
+        Some(Ok(OreInstruction::Mine)) => CU_LIMIT_MINE,
+        Some(Ok(OreInstruction::Claim)) => CU_LIMIT_CLAIM,
+        Some(Ok(OreInstruction::Reset)) => CU_LIMIT_RESET,
+        _ => CU_LIMIT_DEFAULT,
+    }
+}
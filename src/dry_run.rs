@@ -0,0 +1,227 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ore::instruction::OreInstruction;
+use solana_program::{
+    instruction::Instruction, native_token::lamports_to_sol, pubkey::Pubkey, system_instruction::SystemInstruction,
+    system_program,
+};
+use solana_sdk::{compute_budget, transaction::VersionedTransaction};
+
+use crate::{
+    chain::Chain,
+    profit::LAMPORTS_PER_SIGNATURE,
+    send_and_confirm::JITO_TIP_ACCOUNT,
+    sender::{SendResult, TransactionSender},
+};
+
+// Compute budget instruction tags, as laid out by the runtime
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Prints what each transaction would do and cost instead of sending it,
+/// simulating it first when asked.
+pub struct DryRunSender {
+    chain: Arc<dyn Chain>,
+    simulate: bool,
+}
+
+impl DryRunSender {
+    pub fn new(chain: Arc<dyn Chain>, simulate: bool) -> Self {
+        Self { chain, simulate }
+    }
+}
+
+#[async_trait]
+impl TransactionSender for DryRunSender {
+    async fn send(&mut self, txs: &[VersionedTransaction]) -> SendResult<Vec<String>> {
+        let mut signatures = Vec::with_capacity(txs.len());
+        let (mut total_tip, mut total_fees) = (0, 0);
+        for tx in txs {
+            let signature = tx.signatures[0].to_string();
+            let summary = TransactionSummary::new(tx);
+            println!("Transaction {}", signature);
+            for line in summary.lines.iter() {
+                println!("  {}", line);
+            }
+            println!(
+                "  Tip: {} SOL, estimated fees: {} SOL",
+                lamports_to_sol(summary.tip),
+                lamports_to_sol(summary.fees)
+            );
+            if self.simulate {
+                match self.chain.simulate_transaction(tx).await {
+                    Ok(None) => println!("  Simulation: ok"),
+                    Ok(Some(err)) => println!("  Simulation failed: {}", err),
+                    Err(err) => println!("  Error simulating transaction: {}", err),
+                }
+            }
+            println!("  Encoded: {}", bs58::encode(bincode::serialize(tx)?).into_string());
+            total_tip += summary.tip;
+            total_fees += summary.fees;
+            signatures.push(signature);
+        }
+        println!(
+            "Dry run: {} transactions not sent, {} SOL in tips and {} SOL in estimated fees\n",
+            txs.len(),
+            lamports_to_sol(total_tip),
+            lamports_to_sol(total_fees)
+        );
+        Ok(signatures)
+    }
+}
+
+/// Decoded instructions of a transaction, with the tip and fees it pays.
+struct TransactionSummary {
+    lines: Vec<String>,
+    tip: u64,
+    fees: u64,
+}
+
+impl TransactionSummary {
+    fn new(tx: &VersionedTransaction) -> Self {
+        let message = &tx.message;
+        let keys = message.static_account_keys();
+        let mut lines = vec![];
+        for (i, ix) in message.instructions().iter().enumerate() {
+            let Some(program_id) = keys.get(ix.program_id_index as usize) else {
+                lines.push(format!("#{} program from a lookup table", i));
+                continue;
+            };
+            lines.push(format!("#{} {}", i, describe_instruction(program_id, &ix.data)));
+            for index in ix.accounts.iter().map(|index| *index as usize) {
+                let address = keys
+                    .get(index)
+                    .map_or("(lookup table)".to_string(), |key| key.to_string());
+                let signer = if message.is_signer(index) { " signer" } else { "" };
+                let writable = if message.is_maybe_writable(index) { " writable" } else { "" };
+                lines.push(format!("    {}{}{}", address, signer, writable));
+            }
+        }
+        let cost = TransactionCost::new(tx);
+        Self {
+            lines,
+            tip: cost.tip,
+            fees: cost.fees,
+        }
+    }
+}
+
+/// What a signed transaction pays when it lands, in lamports.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransactionCost {
+    /// Transfers to the Jito tip account.
+    pub tip: u64,
+    /// Signature fees plus the priority fee on the requested compute units.
+    pub fees: u64,
+}
+
+impl TransactionCost {
+    pub fn new(tx: &VersionedTransaction) -> Self {
+        let message = &tx.message;
+        let keys = message.static_account_keys();
+        let mut tip = 0;
+        let (mut compute_units, mut compute_unit_price) = (None, 0u64);
+        for ix in message.instructions() {
+            let Some(program_id) = keys.get(ix.program_id_index as usize) else {
+                continue;
+            };
+            if program_id.eq(&system_program::ID) {
+                if let Ok(SystemInstruction::Transfer { lamports }) = bincode::deserialize(&ix.data) {
+                    let to = ix.accounts.get(1).and_then(|index| keys.get(*index as usize));
+                    if to.eq(&Some(&JITO_TIP_ACCOUNT)) {
+                        tip += lamports;
+                    }
+                }
+            }
+            if program_id.eq(&compute_budget::ID) {
+                match decode_compute_budget(&ix.data) {
+                    Some((SET_COMPUTE_UNIT_LIMIT, units)) => compute_units = Some(units),
+                    Some((SET_COMPUTE_UNIT_PRICE, price)) => compute_unit_price = price,
+                    _ => {}
+                }
+            }
+        }
+
+        // The priority fee is charged on the requested compute units, in microlamports
+        let signatures = message.header().num_required_signatures as u64;
+        let signature_fees = signatures.saturating_mul(LAMPORTS_PER_SIGNATURE);
+        let priority_fee = priority_fee(compute_units.unwrap_or(0), compute_unit_price);
+        Self {
+            tip,
+            fees: signature_fees.saturating_add(priority_fee),
+        }
+    }
+
+    pub fn add(&mut self, other: TransactionCost) {
+        self.tip = self.tip.saturating_add(other.tip);
+        self.fees = self.fees.saturating_add(other.fees);
+    }
+}
+
+/// A readable name for an instruction of a program the miner sends to.
+fn describe_instruction(program_id: &Pubkey, data: &[u8]) -> String {
+    if program_id.eq(&ore::ID) {
+        return match data.first().map(|tag| OreInstruction::try_from(*tag)) {
+            Some(Ok(ix)) => format!("Ore {:?}", ix),
+            _ => "Ore unknown instruction".to_string(),
+        };
+    }
+    if program_id.eq(&system_program::ID) {
+        return match bincode::deserialize::<SystemInstruction>(data) {
+            Ok(ix) => format!("System {:?}", ix),
+            Err(_) => "System unknown instruction".to_string(),
+        };
+    }
+    if program_id.eq(&compute_budget::ID) {
+        return match decode_compute_budget(data) {
+            Some((SET_COMPUTE_UNIT_LIMIT, units)) => {
+                format!("ComputeBudget SetComputeUnitLimit({})", units)
+            }
+            Some((SET_COMPUTE_UNIT_PRICE, price)) => {
+                format!("ComputeBudget SetComputeUnitPrice({})", price)
+            }
+            _ => "ComputeBudget unknown instruction".to_string(),
+        };
+    }
+    if program_id.eq(&spl_token::id()) {
+        return format!("Token {} bytes", data.len());
+    }
+    if program_id.eq(&spl_associated_token_account::id()) {
+        return match data.first() {
+            None | Some(0) => "AssociatedToken Create".to_string(),
+            Some(1) => "AssociatedToken CreateIdempotent".to_string(),
+            _ => "AssociatedToken unknown instruction".to_string(),
+        };
+    }
+    format!("{} {} bytes", program_id, data.len())
+}
+
+/// The priority fee in lamports of requesting `compute_units` at `compute_unit_price`
+/// microlamports each.
+pub fn priority_fee(compute_units: u64, compute_unit_price: u64) -> u64 {
+    compute_units
+        .saturating_mul(compute_unit_price)
+        .div_ceil(1_000_000)
+}
+
+/// The compute unit limit instructions set, if any of them does.
+pub fn compute_unit_limit(ixs: &[Instruction]) -> Option<u64> {
+    ixs.iter()
+        .filter(|ix| ix.program_id.eq(&compute_budget::ID))
+        .find_map(|ix| match decode_compute_budget(&ix.data) {
+            Some((SET_COMPUTE_UNIT_LIMIT, units)) => Some(units),
+            _ => None,
+        })
+}
+
+/// The tag and argument of a compute unit limit or price instruction.
+fn decode_compute_budget(data: &[u8]) -> Option<(u8, u64)> {
+    let (tag, arg) = data.split_first()?;
+    let arg = match *tag {
+        SET_COMPUTE_UNIT_LIMIT => u32::from_le_bytes(arg.try_into().ok()?) as u64,
+        SET_COMPUTE_UNIT_PRICE => u64::from_le_bytes(arg.try_into().ok()?),
+        _ => return None,
+    };
+    Some((*tag, arg))
+}
@@ -0,0 +1,151 @@
+//! An in-memory chain for exercising command logic offline.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use async_trait::async_trait;
+use solana_client::client_error::{ClientErrorKind, Result as ClientResult};
+use solana_program::{hash::Hash, pubkey::Pubkey, rent::Rent};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+};
+
+use crate::chain::Chain;
+
+// Slot the fake chain is always at
+pub const FAKE_SLOT: u64 = 1_000;
+
+/// Serves accounts from memory and records transactions instead of sending them.
+#[derive(Default)]
+pub struct FakeChain {
+    accounts: Mutex<HashMap<Pubkey, Account>>,
+    blockhash: Hash,
+    slot: u64,
+    sent: Mutex<Vec<VersionedTransaction>>,
+    /// Error every simulation and send fails with, if any.
+    simulation_error: Mutex<Option<TransactionError>>,
+    /// Sends that go through before the simulation error applies.
+    failing_after: Mutex<usize>,
+    /// Errors that simulations and sends of transactions using an account fail with.
+    failing_accounts: Mutex<HashMap<Pubkey, TransactionError>>,
+}
+
+impl FakeChain {
+    pub fn new() -> Self {
+        Self {
+            blockhash: Hash::new_unique(),
+            slot: FAKE_SLOT,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_account(&self, address: Pubkey, account: Account) {
+        self.accounts.lock().unwrap().insert(address, account);
+    }
+
+    /// Makes every simulation and send fail with `err`.
+    pub fn fail_transactions(&self, err: TransactionError) {
+        self.fail_transactions_after(0, err);
+    }
+
+    /// Lets `sends` transactions go through in total, then fails the rest with `err`.
+    pub fn fail_transactions_after(&self, sends: usize, err: TransactionError) {
+        *self.failing_after.lock().unwrap() = sends;
+        *self.simulation_error.lock().unwrap() = Some(err);
+    }
+
+    /// Makes every simulation and send of a transaction using `address` fail with `err`.
+    pub fn fail_transactions_using(&self, address: Pubkey, err: TransactionError) {
+        self.failing_accounts.lock().unwrap().insert(address, err);
+    }
+
+    /// Transactions sent so far, in order.
+    pub fn sent(&self) -> Vec<VersionedTransaction> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Chain for FakeChain {
+    async fn get_account(&self, address: &Pubkey) -> ClientResult<Option<Account>> {
+        Ok(self.accounts.lock().unwrap().get(address).cloned())
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<Option<Account>>> {
+        let accounts = self.accounts.lock().unwrap();
+        Ok(addresses
+            .iter()
+            .map(|address| accounts.get(address).cloned())
+            .collect())
+    }
+
+    async fn get_multiple_accounts_with_commitment(
+        &self,
+        addresses: &[Pubkey],
+        _commitment: CommitmentConfig,
+    ) -> ClientResult<Vec<Option<Account>>> {
+        self.get_multiple_accounts(addresses).await
+    }
+
+    async fn get_slot(&self, _commitment: CommitmentConfig) -> ClientResult<u64> {
+        Ok(self.slot)
+    }
+
+    async fn get_balance(&self, address: &Pubkey) -> ClientResult<u64> {
+        Ok(self
+            .get_account(address)
+            .await?
+            .map_or(0, |account| account.lamports))
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        Ok(Rent::default().minimum_balance(data_len))
+    }
+
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        Ok(self.blockhash)
+    }
+
+    async fn send_transaction(&self, tx: &VersionedTransaction) -> ClientResult<Signature> {
+        if let Some(err) = self.simulate_transaction(tx).await? {
+            return Err(ClientErrorKind::TransactionError(err).into());
+        }
+        self.sent.lock().unwrap().push(tx.clone());
+        Ok(tx.signatures[0])
+    }
+
+    async fn simulate_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> ClientResult<Option<TransactionError>> {
+        let failing_accounts = self.failing_accounts.lock().unwrap();
+        let failing = tx
+            .message
+            .static_account_keys()
+            .iter()
+            .find_map(|address| failing_accounts.get(address));
+        if let Some(err) = failing {
+            return Ok(Some(err.clone()));
+        }
+        if self.sent.lock().unwrap().len().lt(&*self.failing_after.lock().unwrap()) {
+            return Ok(None);
+        }
+        Ok(self.simulation_error.lock().unwrap().clone())
+    }
+
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<Result<(), TransactionError>>> {
+        let sent = self.sent.lock().unwrap();
+        Ok(sent
+            .iter()
+            .any(|tx| tx.signatures[0].eq(signature))
+            .then_some(Ok(())))
+    }
+}
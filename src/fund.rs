@@ -0,0 +1,64 @@
+use log::{error, info};
+use solana_program::{native_token::lamports_to_sol, pubkey::Pubkey};
+use solana_sdk::{native_token::sol_to_lamports, signature::Signer, system_instruction::transfer};
+
+use crate::{utils::get_multiple_accounts_batched, Miner};
+
+impl Miner {
+    pub async fn fund(&self, target: f64) {
+        let pubkeys: Vec<Pubkey> = self
+            .signers()
+            .iter()
+            .map(|signer| signer.pubkey())
+            .collect();
+        if let Err(err) = self.fund_wallets(&pubkeys, sol_to_lamports(target)).await {
+            error!("Error funding wallets: {}", err);
+        }
+    }
+
+    /// Tops up every wallet below `target` lamports from the fee payer, returning the
+    /// lamports sent in total.
+    pub async fn fund_wallets(
+        &self,
+        pubkeys: &[Pubkey],
+        target: u64,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let feepayer = self.feepayer();
+
+        // Fetch every miner wallet in batched requests
+        let wallets = get_multiple_accounts_batched(self.chain.as_ref(), pubkeys)
+            .await
+            .map_err(|err| format!("could not fetch the miner accounts: {}", err))?;
+
+        // Top up every wallet below the target from the fee payer
+        let mut ixs = Vec::new();
+        let mut total = 0;
+        for (pubkey, wallet) in pubkeys.iter().zip(wallets) {
+            let balance = wallet.map_or(0, |account| account.lamports);
+            let amount = target.saturating_sub(balance);
+            if amount.gt(&0) {
+                info!(wallet:% = pubkey; "+{} SOL", lamports_to_sol(amount));
+                ixs.push(transfer(&feepayer.pubkey(), pubkey, amount));
+                total += amount;
+            }
+        }
+        if ixs.is_empty() {
+            info!(
+                "All wallets hold at least {} SOL, returning.",
+                lamports_to_sol(target)
+            );
+            return Ok(0);
+        }
+
+        // Only the fee payer signs the transfers
+        let uuids = self.send_and_confirm(&ixs, &[]).await?;
+        info!(bundle = uuids.join(","); "Funding bundles sent");
+        info!(
+            "Funded {} wallets with {} SOL from {}",
+            ixs.len(),
+            lamports_to_sol(total),
+            feepayer.pubkey()
+        );
+        Ok(total)
+    }
+}
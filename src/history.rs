@@ -0,0 +1,445 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use solana_program::{native_token::lamports_to_sol, pubkey::Pubkey};
+
+use crate::{
+    output::{self, OreAmount, OutputFormat, QueryOutput},
+    Miner,
+};
+
+/// What the miner did, one JSON object per line of the ledger.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LedgerEvent {
+    /// A wallet's mine instruction was sent.
+    Round {
+        timestamp: i64,
+        wallet: String,
+        /// Proof hash the solution was found for.
+        proof_hash: String,
+        /// The proof's total rewards when the round started, in ORE base units,
+        /// to carry reward deltas across restarts. Missing from older ledgers.
+        #[serde(default)]
+        total_rewards: Option<u64>,
+        /// Solution sent to the program.
+        hash: String,
+        nonce: u64,
+        difficulty: String,
+        bus: u64,
+        /// Bundles the round's transactions were sent in.
+        bundles: Vec<String>,
+        /// This wallet's share of the round's tips, in lamports.
+        tip: u64,
+        /// This wallet's share of the round's signature and priority fees, in lamports.
+        fee: u64,
+    },
+    /// A sent bundle landed, failed or timed out.
+    Outcome {
+        timestamp: i64,
+        bundle: String,
+        landed: bool,
+        slot: Option<u64>,
+    },
+    /// A wallet's proof earned rewards since the previous round.
+    Reward {
+        timestamp: i64,
+        wallet: String,
+        /// Increase in the proof's total rewards, in ORE base units.
+        delta: u64,
+    },
+}
+
+impl LedgerEvent {
+    pub fn timestamp(&self) -> i64 {
+        match self {
+            LedgerEvent::Round { timestamp, .. }
+            | LedgerEvent::Outcome { timestamp, .. }
+            | LedgerEvent::Reward { timestamp, .. } => *timestamp,
+        }
+    }
+}
+
+/// An append-only JSONL file of ledger events.
+pub struct Ledger {
+    path: PathBuf,
+}
+
+impl Ledger {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Appends an event. A ledger that cannot be written must not stop the miner.
+    pub fn append(&self, event: LedgerEvent) {
+        let line = serde_json::to_string(&event).expect("Failed to serialize ledger event");
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", line));
+        if let Err(err) = written {
+            warn!("Error writing to ledger {}: {}", self.path.display(), err);
+        }
+    }
+
+    /// Every event in the ledger, skipping lines that do not parse. A missing ledger is empty.
+    pub fn read(&self) -> std::io::Result<Vec<LedgerEvent>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind().eq(&std::io::ErrorKind::NotFound) => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+        let mut events = vec![];
+        for line in BufReader::new(file).lines() {
+            match serde_json::from_str(&line?) {
+                Ok(event) => events.push(event),
+                Err(err) => warn!("Skipping unreadable ledger line: {}", err),
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// Now, as stored in the ledger.
+pub fn now() -> i64 {
+    Utc::now().timestamp()
+}
+
+/// The UTC day of a ledger timestamp.
+pub fn day(timestamp: i64) -> NaiveDate {
+    DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .date_naive()
+}
+
+/// Wallet `index`'s share of `total` split between `wallets`, the first wallets taking
+/// the remainder so the shares add up to the total.
+pub fn share(total: u64, wallets: usize, index: usize) -> u64 {
+    let wallets = wallets.max(1) as u64;
+    let remainder = (index as u64).lt(&(total % wallets)) as u64;
+    total / wallets + remainder
+}
+
+/// The proof total rewards last recorded for each wallet, to measure the next reward from.
+pub fn last_total_rewards(events: &[LedgerEvent]) -> HashMap<Pubkey, u64> {
+    let mut totals = HashMap::new();
+    for event in events {
+        if let LedgerEvent::Round {
+            wallet,
+            total_rewards: Some(total_rewards),
+            ..
+        } = event
+        {
+            if let Ok(wallet) = Pubkey::from_str(wallet) {
+                totals.insert(wallet, *total_rewards);
+            }
+        }
+    }
+    totals
+}
+
+/// Whether an event happened on `since` or later, when given.
+pub fn on_or_after(event: &LedgerEvent, since: Option<NaiveDate>) -> bool {
+    since.map_or(true, |since| day(event.timestamp()).ge(&since))
+}
+
+/// Whether a round's bundles landed, as far as the ledger knows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundResult {
+    /// At least one of its bundles landed.
+    Landed,
+    /// Every one of its bundles failed or timed out.
+    Failed,
+    /// No outcome was recorded for its bundles yet.
+    Unknown,
+}
+
+/// Rounds, earnings and costs over some slice of the ledger.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Summary {
+    pub rounds: u64,
+    pub landed: u64,
+    pub failed: u64,
+    /// ORE earned, in base units.
+    pub reward: u64,
+    /// Lamports tipped by rounds that did not fail.
+    pub tips: u64,
+    /// Lamports paid in signature fees by rounds that did not fail.
+    pub fees: u64,
+}
+
+impl Summary {
+    pub fn add_round(&mut self, result: RoundResult, tip: u64, fee: u64) {
+        self.rounds += 1;
+        match result {
+            RoundResult::Landed => self.landed += 1,
+            RoundResult::Failed => {
+                self.failed += 1;
+                return;
+            }
+            RoundResult::Unknown => {}
+        }
+        self.tips += tip;
+        self.fees += fee;
+    }
+
+    pub fn add_reward(&mut self, delta: u64) {
+        self.reward += delta;
+    }
+}
+
+/// The result of each sent bundle, from the ledger's outcome events.
+pub fn bundle_outcomes(events: &[LedgerEvent]) -> HashMap<&str, bool> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            LedgerEvent::Outcome { bundle, landed, .. } => Some((bundle.as_str(), *landed)),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn round_result(bundles: &[String], outcomes: &HashMap<&str, bool>) -> RoundResult {
+    let results: Vec<_> = bundles
+        .iter()
+        .map(|bundle| outcomes.get(bundle.as_str()))
+        .collect();
+    if results.iter().any(|landed| landed.eq(&Some(&true))) {
+        RoundResult::Landed
+    } else if !results.is_empty() && results.iter().all(|landed| landed.eq(&Some(&false))) {
+        RoundResult::Failed
+    } else {
+        RoundResult::Unknown
+    }
+}
+
+/// Ledger events summarized per day and per wallet.
+pub fn summarize(
+    events: &[LedgerEvent],
+) -> (BTreeMap<NaiveDate, Summary>, BTreeMap<String, Summary>) {
+    let outcomes = bundle_outcomes(events);
+    let mut days: BTreeMap<NaiveDate, Summary> = BTreeMap::new();
+    let mut wallets: BTreeMap<String, Summary> = BTreeMap::new();
+    for event in events {
+        let day = days.entry(day(event.timestamp())).or_default();
+        match event {
+            LedgerEvent::Round {
+                wallet,
+                bundles,
+                tip,
+                fee,
+                ..
+            } => {
+                let result = round_result(bundles, &outcomes);
+                day.add_round(result, *tip, *fee);
+                wallets
+                    .entry(wallet.clone())
+                    .or_default()
+                    .add_round(result, *tip, *fee);
+            }
+            LedgerEvent::Reward { wallet, delta, .. } => {
+                day.add_reward(*delta);
+                wallets.entry(wallet.clone()).or_default().add_reward(*delta);
+            }
+            LedgerEvent::Outcome { .. } => {}
+        }
+    }
+    (days, wallets)
+}
+
+/// One round listed by `history --rounds`.
+#[derive(Serialize)]
+pub struct RoundOutput {
+    pub time: String,
+    pub wallet: String,
+    pub bus: u64,
+    pub nonce: u64,
+    /// Landed, Failed or Unknown.
+    pub result: String,
+}
+
+/// The totals of one day or one wallet.
+#[derive(Serialize)]
+pub struct SummaryOutput {
+    /// The UTC day or the wallet summarized.
+    pub label: String,
+    pub rounds: u64,
+    pub landed: u64,
+    pub failed: u64,
+    pub reward: OreAmount,
+    pub tips_sol: f64,
+    pub fees_sol: f64,
+}
+
+impl SummaryOutput {
+    fn new(label: String, summary: &Summary) -> Self {
+        Self {
+            label,
+            rounds: summary.rounds,
+            landed: summary.landed,
+            failed: summary.failed,
+            reward: OreAmount::new(summary.reward),
+            tips_sol: lamports_to_sol(summary.tips),
+            fees_sol: lamports_to_sol(summary.fees),
+        }
+    }
+}
+
+/// The ledger summarized per day and per wallet. Tables and CSV hold the summaries,
+/// the listed rounds are printed as text and JSON only.
+#[derive(Serialize)]
+pub struct HistoryOutput {
+    pub ledger: String,
+    /// Every round, when asked for.
+    pub rounds: Vec<RoundOutput>,
+    pub days: Vec<SummaryOutput>,
+    pub wallets: Vec<SummaryOutput>,
+}
+
+impl QueryOutput for HistoryOutput {
+    fn text(&self) -> Vec<String> {
+        if self.days.is_empty() {
+            return vec![format!("No history in {}", self.ledger)];
+        }
+        let mut lines = vec![];
+        if !self.rounds.is_empty() {
+            lines.push(format!(
+                "{:<20} {:<44} {:>3} {:>20} {:<8}",
+                "TIME", "WALLET", "BUS", "NONCE", "RESULT"
+            ));
+            for round in self.rounds.iter() {
+                lines.push(format!(
+                    "{:<20} {:<44} {:>3} {:>20} {:<8}",
+                    round.time, round.wallet, round.bus, round.nonce, round.result
+                ));
+            }
+            lines.push(String::new());
+        }
+        lines.push(summary_header("DAY"));
+        lines.extend(self.days.iter().map(summary_line));
+        lines.push(String::new());
+        lines.push(summary_header("WALLET"));
+        lines.extend(self.wallets.iter().map(summary_line));
+        lines
+    }
+
+    fn table(&self) -> (Vec<&'static str>, Vec<Vec<String>>) {
+        let days = self.days.iter().map(|summary| ("day", summary));
+        let wallets = self.wallets.iter().map(|summary| ("wallet", summary));
+        let rows = days
+            .chain(wallets)
+            .map(|(group, summary)| {
+                vec![
+                    group.to_string(),
+                    summary.label.clone(),
+                    summary.rounds.to_string(),
+                    summary.landed.to_string(),
+                    summary.failed.to_string(),
+                    summary.reward.to_string(),
+                    summary.tips_sol.to_string(),
+                    summary.fees_sol.to_string(),
+                ]
+            })
+            .collect();
+        let header = vec![
+            "GROUP", "LABEL", "ROUNDS", "LANDED", "FAILED", "ORE", "TIPS SOL", "FEES SOL",
+        ];
+        (header, rows)
+    }
+}
+
+fn summary_header(label: &str) -> String {
+    format!(
+        "{:<44} {:>7} {:>7} {:>7} {:>14} {:>12} {:>12}",
+        label, "ROUNDS", "LANDED", "FAILED", "ORE", "TIPS SOL", "FEES SOL"
+    )
+}
+
+fn summary_line(summary: &SummaryOutput) -> String {
+    format!(
+        "{:<44} {:>7} {:>7} {:>7} {:>14} {:>12} {:>12}",
+        summary.label,
+        summary.rounds,
+        summary.landed,
+        summary.failed,
+        summary.reward,
+        summary.tips_sol,
+        summary.fees_sol
+    )
+}
+
+impl Miner {
+    pub fn history(
+        &self,
+        ledger: PathBuf,
+        wallet: Option<String>,
+        since: Option<NaiveDate>,
+        rounds: bool,
+        format: OutputFormat,
+    ) {
+        let events = match Ledger::new(ledger.clone()).read() {
+            Ok(events) => events,
+            Err(err) => {
+                output::fail(format!("Could not read ledger {}: {}", ledger.display(), err))
+            }
+        };
+        let events: Vec<_> = events
+            .into_iter()
+            .filter(|event| on_or_after(event, since))
+            .filter(|event| match (event, wallet.as_ref()) {
+                (LedgerEvent::Round { wallet, .. }, Some(filter))
+                | (LedgerEvent::Reward { wallet, .. }, Some(filter)) => wallet.eq(filter),
+                _ => true,
+            })
+            .collect();
+
+        let mut listed = vec![];
+        if rounds {
+            let outcomes = bundle_outcomes(&events);
+            for event in events.iter() {
+                if let LedgerEvent::Round {
+                    timestamp,
+                    wallet,
+                    bus,
+                    nonce,
+                    bundles,
+                    ..
+                } = event
+                {
+                    let time = DateTime::from_timestamp(*timestamp, 0).unwrap_or_default();
+                    listed.push(RoundOutput {
+                        time: time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                        wallet: wallet.clone(),
+                        bus: *bus,
+                        nonce: *nonce,
+                        result: format!("{:?}", round_result(bundles, &outcomes)),
+                    });
+                }
+            }
+        }
+
+        let (days, wallets) = summarize(&events);
+        let history = HistoryOutput {
+            ledger: ledger.display().to_string(),
+            rounds: listed,
+            days: days
+                .iter()
+                .map(|(day, summary)| SummaryOutput::new(day.to_string(), summary))
+                .collect(),
+            wallets: wallets
+                .iter()
+                .map(|(wallet, summary)| SummaryOutput::new(wallet.clone(), summary))
+                .collect(),
+        };
+        output::print(&history, format);
+    }
+}
@@ -0,0 +1,121 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::transaction::VersionedTransaction;
+use thiserror::Error;
+
+// Most bundle ids the status methods accept in one request
+pub const MAX_BUNDLE_STATUS_IDS: usize = 5;
+
+#[derive(Debug, Error)]
+pub enum JitoHttpError {
+    #[error("http error {0}")]
+    HttpError(#[from] reqwest::Error),
+    #[error("rpc error {0}")]
+    RpcError(Value),
+    #[error("unexpected response {0}")]
+    UnexpectedResponse(Value),
+}
+
+pub type JitoHttpResult<T> = Result<T, JitoHttpError>;
+
+/// Status of a bundle submitted in the last five minutes.
+#[derive(Debug, Deserialize)]
+pub struct InflightBundleStatus {
+    pub bundle_id: String,
+    /// One of `Invalid`, `Pending`, `Failed` or `Landed`.
+    pub status: String,
+    pub landed_slot: Option<u64>,
+}
+
+/// Status of a bundle that landed on chain.
+#[derive(Debug, Deserialize)]
+pub struct LandedBundleStatus {
+    pub bundle_id: String,
+    pub transactions: Vec<String>,
+    pub slot: u64,
+    pub confirmation_status: Option<String>,
+    pub err: Value,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    value: T,
+}
+
+/// Client for the block engine's JSON-RPC bundle API, which needs no auth keypair.
+pub struct JitoHttpClient {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl JitoHttpClient {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn request(&self, method: &str, params: Value) -> JitoHttpResult<Value> {
+        let response: Value = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await?
+            .json()
+            .await?;
+        match response.get("error") {
+            Some(error) => Err(JitoHttpError::RpcError(error.clone())),
+            None => Ok(response["result"].clone()),
+        }
+    }
+
+    /// Sends up to 5 signed transactions as a bundle, returning its id.
+    pub async fn send_bundle(&self, transactions: &[VersionedTransaction]) -> JitoHttpResult<String> {
+        // The API only accepts base-58 encoded transactions
+        let encoded: Vec<String> = transactions
+            .iter()
+            .map(|tx| bs58::encode(bincode::serialize(tx).unwrap()).into_string())
+            .collect();
+        let result = self.request("sendBundle", json!([encoded])).await?;
+        match result.as_str() {
+            Some(uuid) => Ok(uuid.to_string()),
+            None => Err(JitoHttpError::UnexpectedResponse(result)),
+        }
+    }
+
+    pub async fn get_tip_accounts(&self) -> JitoHttpResult<Vec<String>> {
+        let result = self.request("getTipAccounts", json!([])).await?;
+        serde_json::from_value(result.clone()).map_err(|_| JitoHttpError::UnexpectedResponse(result))
+    }
+
+    /// Statuses of up to `MAX_BUNDLE_STATUS_IDS` recently sent bundles, `None` for bundles the
+    /// block engine does not know.
+    pub async fn get_inflight_bundle_statuses(
+        &self,
+        uuids: &[String],
+    ) -> JitoHttpResult<Vec<Option<InflightBundleStatus>>> {
+        let result = self.request("getInflightBundleStatuses", json!([uuids])).await?;
+        serde_json::from_value::<RpcResponse<_>>(result.clone())
+            .map(|response| response.value)
+            .map_err(|_| JitoHttpError::UnexpectedResponse(result))
+    }
+
+    /// Statuses of up to `MAX_BUNDLE_STATUS_IDS` landed bundles, `None` for bundles that have
+    /// not landed.
+    pub async fn get_bundle_statuses(
+        &self,
+        uuids: &[String],
+    ) -> JitoHttpResult<Vec<Option<LandedBundleStatus>>> {
+        let result = self.request("getBundleStatuses", json!([uuids])).await?;
+        serde_json::from_value::<RpcResponse<_>>(result.clone())
+            .map(|response| response.value)
+            .map_err(|_| JitoHttpError::UnexpectedResponse(result))
+    }
+}
@@ -0,0 +1,263 @@
+//! Leveled, timestamped log lines on stderr and optionally in a size-rotated file,
+//! plus a throttled status line for progress that would otherwise flood the log.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{stderr, IsTerminal, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use chrono::{SecondsFormat, Utc};
+use clap::ValueEnum;
+use log::{
+    kv::{Key, Value, VisitSource},
+    Level, LevelFilter, Log, Metadata, Record,
+};
+use serde_json::{Map, Value as JsonValue};
+
+// Log records from other crates are only shown at this level or above
+const DEPENDENCY_LEVEL: Level = Level::Warn;
+
+// Name of this binary's crate, which the targets of its records start with
+const CRATE_NAME: &str = env!("CARGO_CRATE_NAME");
+
+// Milliseconds between redraws of the status line
+const STATUS_INTERVAL: u64 = 250;
+
+// Whether the status line is drawn at all, which needs a terminal and text logs
+static STATUS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+// When the status line was last drawn, `None` while it is cleared
+static STATUS_DRAWN_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// How log lines are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// `<timestamp> <LEVEL> <target> <message> key=value ...`
+    Text,
+    /// One JSON object per line, with context fields as keys.
+    Json,
+}
+
+pub struct LogConfig {
+    pub level: LevelFilter,
+    pub format: LogFormat,
+    /// File to also write the log to, rotated once it grows past `max_file_size` bytes.
+    pub file: Option<PathBuf>,
+    pub max_file_size: u64,
+    /// Rotated files to keep besides the current one.
+    pub max_files: usize,
+}
+
+/// Installs the logger for the `log` macros.
+pub fn init(config: LogConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let file = match config.file {
+        Some(path) => Some(Mutex::new(RotatingFile::open(
+            path,
+            config.max_file_size,
+            config.max_files,
+        )?)),
+        None => None,
+    };
+    let status_enabled = config.format.eq(&LogFormat::Text) && stderr().is_terminal();
+    STATUS_ENABLED.store(status_enabled, Ordering::Relaxed);
+    log::set_boxed_logger(Box::new(Logger {
+        level: config.level,
+        format: config.format,
+        file,
+    }))?;
+    log::set_max_level(config.level.max(DEPENDENCY_LEVEL.to_level_filter()));
+    Ok(())
+}
+
+/// Redraws the status line on stderr, at most every `STATUS_INTERVAL` milliseconds.
+pub fn status(text: &str) {
+    if !STATUS_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut drawn_at = STATUS_DRAWN_AT.lock().unwrap();
+    if drawn_at.is_some_and(|at| at.elapsed().lt(&Duration::from_millis(STATUS_INTERVAL))) {
+        return;
+    }
+    eprint!("\r\x1b[2K{}", text);
+    *drawn_at = Some(Instant::now());
+}
+
+/// Clears the status line so the next line starts at the beginning of the terminal.
+fn clear_status() {
+    let mut drawn_at = STATUS_DRAWN_AT.lock().unwrap();
+    if drawn_at.take().is_some() {
+        eprint!("\r\x1b[2K");
+    }
+}
+
+struct Logger {
+    level: LevelFilter,
+    format: LogFormat,
+    file: Option<Mutex<RotatingFile>>,
+}
+
+impl Log for Logger {
+    /// Whether a record could be shown. Only its source file tells whether it is this
+    /// binary's, which `log` checks for in [`Logger::log`].
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if is_crate_target(metadata.target()) {
+            metadata.level().le(&self.level)
+        } else {
+            metadata.level().le(&DEPENDENCY_LEVEL)
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        let level = if is_own_record(record.target(), record.file()) {
+            self.level
+        } else {
+            DEPENDENCY_LEVEL.to_level_filter()
+        };
+        if record.level().gt(&level) {
+            return;
+        }
+        let line = match self.format {
+            LogFormat::Text => text_line(record),
+            LogFormat::Json => json_line(record),
+        };
+        clear_status();
+        eprintln!("{}", line);
+        if let Some(file) = self.file.as_ref() {
+            // A log that cannot be written must not stop the miner
+            file.lock().unwrap().write_line(&line).ok();
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.as_ref() {
+            file.lock().unwrap().file.flush().ok();
+        }
+    }
+}
+
+fn is_crate_target(target: &str) -> bool {
+    target
+        .strip_prefix(CRATE_NAME)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+/// Whether a record was logged by this binary. Its crate is named `ore` like the ore-program
+/// library, so both log under `ore::` targets, but only this binary's sources are compiled
+/// from the directory this file is in.
+pub fn is_own_record(target: &str, file: Option<&str>) -> bool {
+    let Some(source_dir) = Path::new(file!()).parent() else {
+        return false;
+    };
+    is_crate_target(target) && file.is_some_and(|file| Path::new(file).starts_with(source_dir))
+}
+
+fn timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn text_line(record: &Record) -> String {
+    let mut line = format!(
+        "{} {:<5} {} {}",
+        timestamp(),
+        record.level(),
+        record.target(),
+        record.args()
+    );
+    let mut fields = TextFields(&mut line);
+    record.key_values().visit(&mut fields).ok();
+    line
+}
+
+fn json_line(record: &Record) -> String {
+    let mut object = Map::new();
+    object.insert("timestamp".to_string(), timestamp().into());
+    object.insert("level".to_string(), record.level().as_str().into());
+    object.insert("target".to_string(), record.target().into());
+    object.insert("message".to_string(), record.args().to_string().into());
+    let mut fields = JsonFields(&mut object);
+    record.key_values().visit(&mut fields).ok();
+    JsonValue::Object(object).to_string()
+}
+
+/// Appends context fields to a text line as ` key=value`.
+struct TextFields<'a>(&'a mut String);
+
+impl<'kvs> VisitSource<'kvs> for TextFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        self.0.push_str(&format!(" {}={}", key, value));
+        Ok(())
+    }
+}
+
+/// Adds context fields to a JSON line as string keys.
+struct JsonFields<'a>(&'a mut Map<String, JsonValue>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        self.0.insert(key.to_string(), value.to_string().into());
+        Ok(())
+    }
+}
+
+/// A log file that is moved to `<path>.1` once it is full, shifting older files up to `max_files`.
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    pub fn open(path: PathBuf, max_size: u64, max_files: usize) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_size,
+            max_files,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size.saturating_add(len).gt(&self.max_size) {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if self.max_files == 0 {
+            self.file.set_len(0)?;
+        } else {
+            // Renaming onto the oldest file replaces it
+            for i in (1..self.max_files).rev() {
+                let from = self.rotated_path(i);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(i + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+}
@@ -1,13 +1,39 @@
+mod alt;
 mod balance;
+#[cfg(test)]
+mod banks_chain;
+mod bundle;
 mod busses;
+mod chain;
 mod claim;
+mod contention;
 mod cu_limits;
+mod dry_run;
+#[cfg(test)]
+mod fake_chain;
+mod fund;
+mod history;
 #[cfg(feature = "admin")]
 mod initialize;
+mod jito_http;
+mod logger;
+mod metrics;
 mod mine;
+#[cfg(test)]
+mod mock_block_engine;
+mod nonce;
+mod output;
+mod payer;
+mod profit;
 mod register;
+mod report;
 mod rewards;
 mod send_and_confirm;
+mod sender;
+mod shredstream;
+mod status;
+mod sweep;
+mod tip;
 mod treasury;
 #[cfg(feature = "admin")]
 mod update_admin;
@@ -15,26 +41,64 @@ mod update_admin;
 mod update_difficulty;
 mod utils;
 mod token_authenticator;
+#[cfg(test)]
+mod tests;
 
 
-use solana_sdk::signature::{Keypair, read_keypair_file};
+use solana_sdk::{pubkey::Pubkey, signature::{Keypair, read_keypair_file}};
 use std::fs::File;
 use std::io::{self, BufRead};
-use std::path::Path;
-use bs58;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+use tokio::sync::watch;
+use chain::{Chain, RpcChain};
+use chrono::NaiveDate;
 use clap::{command, Parser, Subcommand};
+use log::LevelFilter;
+use logger::{LogConfig, LogFormat};
+use metrics::{MeteredChain, Metrics};
+use output::OutputFormat;
+use send_and_confirm::BundleTransport;
+use sender::SenderKind;
 
 
 struct Miner {
     pub auth_filepath: Option<String>,
     pub feepayer_filepath: Option<String>,
-    //pub priority_fee: u64,
-    pub cluster: String,
+    /// Microlamports per compute unit paid by senders that do not tip.
+    pub priority_fee: u64,
+    /// Reads and writes chain state, over RPC to the cluster unless replaced in tests.
+    pub chain: Arc<dyn Chain>,
+    /// Counters and gauges served at `/metrics` while mining.
+    pub metrics: Arc<Metrics>,
     pub jito_fee: u64,
+    /// Tip paid by the next bundle. Starts at `jito_fee` and follows the tip controller.
+    pub jito_tip: AtomicU64,
     pub jito_enable: bool,
     pub be_url: String,
+    /// Most instructions to pack into a single transaction, besides the tip.
+    /// `None` packs as many as fit the transaction's size and compute limits.
+    pub chunk_size: Option<usize>,
+    /// Address lookup table to compile transactions against.
+    pub lookup_table: Option<Pubkey>,
+    /// Sign transactions with the fee payer's nonce accounts instead of a recent blockhash.
+    pub durable_nonce: bool,
+    /// Send the tip as a final transaction of its own, signed only by the fee payer.
+    pub separate_tip: bool,
+    /// How bundles reach the block engine.
+    pub bundle_transport: BundleTransport,
+    /// How signed transactions reach the chain.
+    pub sender: SenderKind,
+    /// Print transactions instead of sending them, packed and tipped as `sender` would send them.
+    pub dry_run: bool,
+    /// Simulate transactions before printing them in a dry run.
+    pub simulate: bool,
+    /// Newest slot seen on the shredstream, 0 until a shred arrives or without one.
+    pub latest_slot: watch::Sender<u64>,
 }
 
 #[derive(Parser, Debug)]
@@ -70,6 +134,112 @@ struct Args {
         global = true
     )]
     be_url: Option<String>,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "TRANSPORT",
+        help = "How to send bundles to the block engine. Defaults to http for /api/v1/ urls, grpc otherwise.",
+        global = true
+    )]
+    bundle_transport: Option<BundleTransport>,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "SENDER",
+        help = "How to send transactions: tipped Jito bundles, or the RPC node with the priority fee",
+        default_value = "jito",
+        global = true
+    )]
+    sender: SenderKind,
+    #[arg(
+        long,
+        help = "Build, sign and print every transaction with its tip and estimated fees, then exit without sending",
+        global = true
+    )]
+    dry_run: bool,
+    #[arg(
+        long,
+        help = "Simulate each transaction of a dry run against the current chain state",
+        requires = "dry_run",
+        global = true
+    )]
+    simulate: bool,
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        help = "Most verbose log level to show: error, warn, info, debug or trace",
+        default_value = "info",
+        global = true
+    )]
+    log_level: LevelFilter,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        help = "Write log lines as text or as one JSON object per line",
+        default_value = "text",
+        global = true
+    )]
+    log_format: LogFormat,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Also write the log to this file, rotating it once it grows too large",
+        global = true
+    )]
+    log_file: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "MEGABYTES",
+        help = "Size at which the log file is rotated",
+        default_value = "10",
+        global = true
+    )]
+    log_max_size: u64,
+    #[arg(
+        long,
+        value_name = "COUNT",
+        help = "Rotated log files to keep",
+        default_value = "5",
+        global = true
+    )]
+    log_max_files: usize,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        help = "Print the results of balance, rewards, status, busses, treasury, history and report as text, a table, CSV or JSON",
+        default_value = "text",
+        global = true
+    )]
+    output: OutputFormat,
+    #[arg(
+        long,
+        value_name = "INSTRUCTION_COUNT",
+        help = "Most instructions to pack into each transaction of a bundle. Defaults to as many as fit its size and compute limits.",
+        value_parser = clap::value_parser!(u64).range(1..),
+        global = true
+    )]
+    chunk_size: Option<u64>,
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        help = "Address lookup table to compile transactions against",
+        global = true
+    )]
+    lookup_table: Option<String>,
+    #[arg(
+        long,
+        help = "Sign transactions with the fee payer's durable nonce accounts so they do not expire",
+        global = true
+    )]
+    durable_nonce: bool,
+    #[arg(
+        long,
+        help = "Send the tip as its own final transaction and skip bundles where nothing else would succeed",
+        global = true
+    )]
+    separate_tip: bool,
     #[clap(
         global = true,
         short = 'C',
@@ -161,9 +331,36 @@ enum Commands {
     #[command(about = "Fetch your balance of unclaimed mining rewards")]
     Rewards(RewardsArgs),
 
+    #[command(about = "Show the balances, rewards and last hash of every miner wallet")]
+    Status(StatusArgs),
+
     #[command(about = "Fetch the treasury account and balance")]
     Treasury(TreasuryArgs),
 
+    #[command(about = "Summarize the mining history ledger")]
+    History(HistoryArgs),
+
+    #[command(about = "Report ORE mined against SOL spent per wallet")]
+    Report(ReportArgs),
+
+    #[command(about = "Move ORE and surplus SOL from every miner wallet into one wallet")]
+    Sweep(SweepArgs),
+
+    #[command(about = "Top up every miner wallet to a target SOL balance from the fee payer")]
+    Fund(FundArgs),
+
+    #[command(about = "Manage the address lookup table used to pack transactions")]
+    Alt(AltArgs),
+
+    #[command(about = "Manage the fee payer's durable nonce accounts")]
+    Nonce(NonceArgs),
+
+    #[command(about = "Query the block engine about bundles")]
+    Bundle(BundleArgs),
+
+    #[command(about = "Keep shreds flowing from the block engine and report new slots as they arrive")]
+    Shredstream(ShredstreamArgs),
+
     #[cfg(feature = "admin")]
     #[command(about = "Initialize the program")]
     Initialize(InitializeArgs),
@@ -200,6 +397,17 @@ struct RewardsArgs {
     pub address: Option<String>,
 }
 
+#[derive(Parser, Debug)]
+struct StatusArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "The ledger written by the mine command, read for each wallet's last hash",
+        default_value = "ore-history.jsonl"
+    )]
+    ledger: PathBuf,
+}
+
 #[derive(Parser, Debug)]
 struct MineArgs {
     #[arg(
@@ -210,17 +418,181 @@ struct MineArgs {
         default_value = "1"
     )]
     threads: u64,
+
+    #[arg(
+        long,
+        value_name = "AMOUNT",
+        help = "Automatically claim a wallet's rewards once they exceed this amount of ORE"
+    )]
+    auto_claim: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "TOKEN_ACCOUNT_ADDRESS",
+        help = "Token account to receive automatically claimed rewards.",
+        requires = "auto_claim"
+    )]
+    auto_claim_beneficiary: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "Warn each round once the fee payer holds less than this amount of SOL",
+        default_value = "0.1"
+    )]
+    payer_warn: f64,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "Pause mining while the fee payer holds less than this amount of SOL",
+        default_value = "0.01"
+    )]
+    payer_min: f64,
+
+    #[arg(
+        long,
+        help = "Skip mine bundles whose tip and fees exceed the value of the ORE they earn"
+    )]
+    profit_guard: bool,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "Price of one ORE in SOL for the profit guard. Fetched from --price-url if not set."
+    )]
+    ore_price: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Jupiter price API endpoint to fetch the ORE/SOL price from",
+        default_value = "https://price.jup.ag/v6/price?ids=oreoN2tQbHXVaZsr3pf66A48miqcBXCDJozganhEJgz&vsToken=So11111111111111111111111111111111111111112"
+    )]
+    price_url: String,
+
+    #[arg(
+        long,
+        help = "Raise the tip after unlanded bundles and lower it after landed ones"
+    )]
+    dynamic_tip: bool,
+
+    #[arg(
+        long,
+        value_name = "LAMPORTS",
+        help = "Lowest tip the dynamic tip will lower to",
+        default_value = "1000"
+    )]
+    min_tip: u64,
+
+    #[arg(
+        long,
+        value_name = "LAMPORTS",
+        help = "Highest tip the dynamic tip will raise to",
+        default_value = "1000000"
+    )]
+    max_tip: u64,
+
+    #[arg(
+        long,
+        help = "Watch pending mine transactions in the block engine mempool and send on the least contended bus"
+    )]
+    avoid_contention: bool,
+
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        help = "Serve Prometheus metrics at http://<ADDRESS>/metrics, e.g. 0.0.0.0:9100"
+    )]
+    metrics_addr: Option<SocketAddr>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "File to append a record of every round, bundle outcome and reward to",
+        default_value = "ore-history.jsonl"
+    )]
+    ledger: PathBuf,
+
+    #[arg(
+        long,
+        value_name = "IP",
+        help = "Receive shreds at this public IP and send each round's bundles as a new slot starts",
+        requires = "shredstream_regions"
+    )]
+    shredstream_ip: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PORT",
+        help = "The UDP port to receive shreds on",
+        default_value = "20000"
+    )]
+    shredstream_port: u16,
+
+    #[arg(
+        long = "shredstream-region",
+        value_name = "REGION",
+        help = "A block engine region to receive shreds from while mining. May be repeated."
+    )]
+    shredstream_regions: Vec<String>,
 }
 
 #[derive(Parser, Debug)]
 struct TreasuryArgs {}
 
+#[derive(Parser, Debug)]
+struct HistoryArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "The ledger written by the mine command",
+        default_value = "ore-history.jsonl"
+    )]
+    ledger: PathBuf,
+
+    #[arg(
+        long,
+        value_name = "PUBKEY",
+        help = "Only summarize the rounds and rewards of this wallet"
+    )]
+    wallet: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "YYYY-MM-DD",
+        help = "Only summarize history from this UTC day on"
+    )]
+    since: Option<NaiveDate>,
+
+    #[arg(long, help = "Also list every round with whether it landed")]
+    rounds: bool,
+}
+
+#[derive(Parser, Debug)]
+struct ReportArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "The ledger written by the mine command",
+        default_value = "ore-history.jsonl"
+    )]
+    ledger: PathBuf,
+
+    #[arg(
+        long,
+        value_name = "YYYY-MM-DD",
+        help = "Only count rounds and rewards from this UTC day on"
+    )]
+    since: Option<NaiveDate>,
+}
+
 #[derive(Parser, Debug)]
 struct ClaimArgs {
     #[arg(
         // long,
         value_name = "AMOUNT",
-        help = "The amount of rewards to claim. Defaults to max."
+        help = "The amount of rewards to claim per wallet. Defaults to max."
     )]
     amount: Option<f64>,
 
@@ -230,6 +602,154 @@ struct ClaimArgs {
         help = "Token account to receive mining rewards."
     )]
     beneficiary: Option<String>,
+
+    #[arg(
+        long,
+        help = "Claim the rewards of every miner wallet in keys.txt",
+        conflicts_with = "wallet"
+    )]
+    all: bool,
+
+    #[arg(
+        long,
+        value_name = "PUBKEY",
+        help = "Miner wallet to claim the rewards of. Can be repeated."
+    )]
+    wallet: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct SweepArgs {
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        help = "The wallet to receive the ORE and SOL of every miner wallet"
+    )]
+    to: String,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "The amount of SOL to leave in each miner wallet, never less than the rent exempt minimum",
+        default_value = "0"
+    )]
+    keep: f64,
+}
+
+#[derive(Parser, Debug)]
+struct FundArgs {
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "The SOL balance to top up each miner wallet to"
+    )]
+    target: f64,
+}
+
+#[derive(Parser, Debug)]
+struct AltArgs {
+    #[command(subcommand)]
+    command: AltCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum AltCommands {
+    #[command(about = "Create a lookup table of the Ore accounts and every miner's proof")]
+    Create(AltCreateArgs),
+
+    #[command(about = "Add any missing Ore accounts and proofs to a lookup table")]
+    Extend(AltExtendArgs),
+}
+
+#[derive(Parser, Debug)]
+struct AltCreateArgs {}
+
+#[derive(Parser, Debug)]
+struct AltExtendArgs {
+    #[arg(
+        value_name = "ADDRESS",
+        help = "The address of the lookup table to extend"
+    )]
+    address: String,
+}
+
+#[derive(Parser, Debug)]
+struct NonceArgs {
+    #[command(subcommand)]
+    command: NonceCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum NonceCommands {
+    #[command(about = "Create the fee payer's nonce accounts, one per transaction of a send")]
+    Init(NonceInitArgs),
+}
+
+#[derive(Parser, Debug)]
+struct NonceInitArgs {
+    #[arg(
+        long,
+        value_name = "COUNT",
+        help = "The number of nonce accounts to create, by default one per transaction a mining round for the keys file packs into"
+    )]
+    count: Option<usize>,
+}
+
+#[derive(Parser, Debug)]
+struct BundleArgs {
+    #[command(subcommand)]
+    command: BundleCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum BundleCommands {
+    #[command(about = "Fetch the accounts the block engine accepts tips to")]
+    TipAccounts(BundleTipAccountsArgs),
+
+    #[command(about = "Fetch the status of a bundle sent in the last five minutes")]
+    Status(BundleStatusArgs),
+}
+
+#[derive(Parser, Debug)]
+struct BundleTipAccountsArgs {}
+
+#[derive(Parser, Debug)]
+struct BundleStatusArgs {
+    #[arg(value_name = "UUID", help = "The uuid of the bundle")]
+    uuid: String,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Poll until the bundle lands or fails, for at most this many seconds"
+    )]
+    wait: Option<u64>,
+}
+
+#[derive(Parser, Debug)]
+struct ShredstreamArgs {
+    #[arg(
+        long,
+        value_name = "IP",
+        help = "The public IP address shreds are sent to, which must match this machine's"
+    )]
+    ip: String,
+
+    #[arg(
+        long,
+        value_name = "PORT",
+        help = "The UDP port to receive shreds on",
+        default_value = "20000"
+    )]
+    port: u16,
+
+    #[arg(
+        long = "region",
+        value_name = "REGION",
+        help = "A block engine region to receive shreds from, e.g. frankfurt. May be repeated.",
+        required = true
+    )]
+    regions: Vec<String>,
 }
 
 #[cfg(feature = "admin")]
@@ -249,6 +769,17 @@ struct UpdateDifficultyArgs {}
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
+    let log_config = LogConfig {
+        level: args.log_level,
+        format: args.log_format,
+        file: args.log_file.clone(),
+        max_file_size: args.log_max_size.saturating_mul(1024 * 1024),
+        max_files: args.log_max_files,
+    };
+    if let Err(err) = logger::init(log_config) {
+        eprintln!("error: Could not set up logging: {}", err);
+        std::process::exit(1);
+    }
 
     // Load the config file from custom path, the default path, or use default config values
     let cli_config = if let Some(config_file) = &args.config_file {
@@ -267,36 +798,102 @@ async fn main() {
     let be_url = args.be_url.unwrap_or(cli_config.json_rpc_url.clone());
     let feepayer_keypair = args.feepayer.unwrap_or(cli_config.keypair_path);
     let auth_keypair = args.auth.unwrap_or(Default::default());
+    let bundle_transport = args
+        .bundle_transport
+        .unwrap_or_else(|| BundleTransport::from_url(&be_url));
+    let lookup_table = args.lookup_table.map(|address| {
+        Pubkey::from_str(&address).unwrap_or_else(|_| {
+            eprintln!("error: Invalid lookup table address `{}`", address);
+            std::process::exit(1);
+        })
+    });
 
     let miner = Arc::new(Miner::new(
-        cluster.clone(),
+        cluster,
         be_url.clone(),
-        //args.priority_fee,
+        args.priority_fee,
         Some(feepayer_keypair),
         Some(auth_keypair),
         args.jito_fee,
-        args.jito_enable
+        args.jito_enable,
+        args.chunk_size.map(|chunk_size| chunk_size as usize),
+        lookup_table,
+        args.durable_nonce,
+        args.separate_tip,
+        bundle_transport,
+        args.sender,
+        args.dry_run,
+        args.simulate,
     ));
 
     // Execute user command.
+    let output = args.output;
     match args.command {
         Commands::Balance(args) => {
-            miner.balance(args.address).await;
+            miner.balance(args.address, output).await;
         }
         Commands::Busses(_) => {
-            miner.busses().await;
+            miner.busses(output).await;
         }
         Commands::Rewards(args) => {
-            miner.rewards(args.address).await;
+            miner.rewards(args.address, output).await;
+        }
+        Commands::Status(args) => {
+            miner.status(args.ledger, output).await;
         }
         Commands::Treasury(_) => {
-            miner.treasury().await;
+            miner.treasury(output).await;
         }
-        Commands::Mine(args) => {
-            miner.mine(args.threads).await;
+        Commands::History(args) => {
+            miner.history(args.ledger, args.wallet, args.since, args.rounds, output);
         }
+        Commands::Report(args) => {
+            miner.report(args.ledger, args.since, output).await;
+        }
+        Commands::Mine(args) => match args.shredstream_ip.clone() {
+            Some(ip) => {
+                let regions = args.shredstream_regions.clone();
+                let port = args.shredstream_port;
+                tokio::join!(miner.receive_shreds(ip, port, regions), miner.mine(args));
+            }
+            None => {
+                miner.mine(args).await;
+            }
+        },
         Commands::Claim(args) => {
-            miner.claim(cluster, args.beneficiary, args.amount).await;
+            miner
+                .claim(args.beneficiary, args.amount, args.all, args.wallet)
+                .await;
+        }
+        Commands::Sweep(args) => {
+            miner.sweep(args.to, args.keep).await;
+        }
+        Commands::Fund(args) => {
+            miner.fund(args.target).await;
+        }
+        Commands::Alt(args) => match args.command {
+            AltCommands::Create(_) => {
+                miner.alt_create().await;
+            }
+            AltCommands::Extend(args) => {
+                miner.alt_extend(args.address).await;
+            }
+        },
+        Commands::Nonce(args) => match args.command {
+            NonceCommands::Init(args) => {
+                miner.nonce_init(args.count).await;
+            }
+        },
+        Commands::Bundle(args) => match args.command {
+            BundleCommands::TipAccounts(_) => {
+                miner.tip_accounts().await;
+            }
+            BundleCommands::Status(args) => {
+                miner.bundle_status(args.uuid, args.wait).await;
+            }
+        },
+        Commands::Shredstream(args) => {
+            miner.shredstream(args.ip, args.port, args.regions).await;
         }
         #[cfg(feature = "admin")]
         Commands::Initialize(_) => {
@@ -313,36 +910,66 @@ async fn main() {
     }
 }
 impl Miner {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         cluster: String, 
         be_url: String, 
-        //priority_fee: u64, 
+        priority_fee: u64,
         feepayer_filepath: Option<String>,
         auth_filepath: Option<String>,
         jito_fee: u64, 
-        jito_enable: bool) -> Self {
-
+        jito_enable: bool,
+        chunk_size: Option<usize>,
+        lookup_table: Option<Pubkey>,
+        durable_nonce: bool,
+        separate_tip: bool,
+        bundle_transport: BundleTransport,
+        sender: SenderKind,
+        dry_run: bool,
+        simulate: bool) -> Self {
+
+        let metrics = Arc::new(Metrics::default());
         Self {
             auth_filepath,
             feepayer_filepath,
-            //priority_fee,
-            cluster,
+            priority_fee,
+            chain: Arc::new(MeteredChain::new(RpcChain::new(cluster), metrics.clone())),
+            metrics,
             be_url,
             jito_fee,
+            jito_tip: AtomicU64::new(jito_fee),
             jito_enable,
+            chunk_size,
+            lookup_table,
+            durable_nonce,
+            separate_tip,
+            bundle_transport,
+            sender,
+            dry_run,
+            simulate,
+            latest_slot: watch::channel(0).0,
         }
     }
 
+    /// Replaces the chain the miner reads and writes, e.g. with a fake.
+    #[cfg(test)]
+    pub fn with_chain(mut self, chain: Arc<dyn Chain>) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    pub fn jito_tip(&self) -> u64 {
+        self.jito_tip.load(Ordering::Relaxed)
+    }
+
     pub fn signers(&self) -> Vec<Keypair> {
         let mut signers = Vec::new();
 
         if let Ok(lines) = read_lines("keys.txt") {
-            for line in lines {
-                if let Ok(key) = line {
-                    let decoded_key = bs58::decode(key).into_vec().unwrap();
-                    let keypair = Keypair::from_bytes(&decoded_key).unwrap();
-                    signers.push(keypair);
-                }
+            for key in lines.map_while(Result::ok) {
+                let decoded_key = bs58::decode(key).into_vec().unwrap();
+                let keypair = Keypair::from_bytes(&decoded_key).unwrap();
+                signers.push(keypair);
             }
         }
         signers
@@ -0,0 +1,287 @@
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fmt::Write,
+    future::Future,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use log::{error, info};
+use solana_client::client_error::Result as ClientResult;
+use solana_program::{hash::Hash, native_token::lamports_to_sol, pubkey::Pubkey};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+};
+
+use crate::chain::Chain;
+
+/// Calls made to the RPC node through one chain method.
+#[derive(Default)]
+struct RpcStats {
+    requests: u64,
+    errors: u64,
+    seconds: f64,
+}
+
+/// What the miner has done so far, rendered in the Prometheus text format.
+#[derive(Default)]
+pub struct Metrics {
+    /// Hashes per second of the last round, by wallet.
+    hashrate: Mutex<BTreeMap<Pubkey, f64>>,
+    solutions: Mutex<BTreeMap<Pubkey, u64>>,
+    /// Claimable ORE at the start of the last round, by wallet.
+    claimable: Mutex<BTreeMap<Pubkey, f64>>,
+    bundles_sent: AtomicU64,
+    bundles_landed: AtomicU64,
+    bundles_dropped: AtomicU64,
+    tips: AtomicU64,
+    fee_payer_balance: AtomicU64,
+    rpc: Mutex<BTreeMap<&'static str, RpcStats>>,
+}
+
+impl Metrics {
+    /// Records a solution found for `wallet` after `hashes` hashes over `elapsed`.
+    pub fn solution_found(&self, wallet: Pubkey, hashes: u64, elapsed: Duration) {
+        let rate = hashes as f64 / elapsed.as_secs_f64().max(1e-3);
+        self.hashrate.lock().unwrap().insert(wallet, rate);
+        *self.solutions.lock().unwrap().entry(wallet).or_default() += 1;
+    }
+
+    pub fn set_claimable(&self, wallet: Pubkey, claimable_rewards: u64) {
+        let claimable = (claimable_rewards as f64) / 10f64.powf(ore::TOKEN_DECIMALS as f64);
+        self.claimable.lock().unwrap().insert(wallet, claimable);
+    }
+
+    pub fn bundles_sent(&self, bundles: usize) {
+        self.bundles_sent.fetch_add(bundles as u64, Ordering::Relaxed);
+    }
+
+    /// Records the final result of a sent bundle, which pays its tip of `tip` lamports
+    /// only if it landed.
+    pub fn bundle_resolved(&self, landed: bool, tip: u64) {
+        if landed {
+            self.bundles_landed.fetch_add(1, Ordering::Relaxed);
+            self.tips.fetch_add(tip, Ordering::Relaxed);
+        } else {
+            self.bundles_dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn set_fee_payer_balance(&self, lamports: u64) {
+        self.fee_payer_balance.store(lamports, Ordering::Relaxed);
+    }
+
+    fn rpc_call(&self, method: &'static str, elapsed: Duration, ok: bool) {
+        let mut rpc = self.rpc.lock().unwrap();
+        let stats = rpc.entry(method).or_default();
+        stats.requests += 1;
+        stats.seconds += elapsed.as_secs_f64();
+        if !ok {
+            stats.errors += 1;
+        }
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let solutions = self
+            .solutions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(wallet, count)| (*wallet, *count as f64))
+            .collect();
+        let wallet_families = [
+            ("ore_hashrate", "gauge", "Hashes per second in the last round.", per_wallet(&self.hashrate)),
+            ("ore_solutions_found_total", "counter", "Valid hashes found.", solutions),
+            ("ore_claimable_rewards", "gauge", "Claimable ORE at the start of the last round.", per_wallet(&self.claimable)),
+        ];
+        for (name, kind, help, values) in wallet_families {
+            write_family(&mut out, name, kind, help);
+            for (wallet, value) in values {
+                writeln!(out, "{}{{wallet=\"{}\"}} {}", name, wallet, value).unwrap();
+            }
+        }
+
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed) as f64;
+        let families = [
+            ("ore_bundles_sent_total", "counter", "Bundles sent.", load(&self.bundles_sent)),
+            ("ore_bundles_landed_total", "counter", "Sent bundles that landed.", load(&self.bundles_landed)),
+            ("ore_bundles_dropped_total", "counter", "Sent bundles that failed to land or timed out.", load(&self.bundles_dropped)),
+            ("ore_tips_lamports_total", "counter", "Lamports tipped by bundles that landed.", load(&self.tips)),
+            ("ore_fee_payer_balance_sol", "gauge", "SOL held by the fee payer.", lamports_to_sol(self.fee_payer_balance.load(Ordering::Relaxed))),
+        ];
+        for (name, kind, help, value) in families {
+            write_family(&mut out, name, kind, help);
+            writeln!(out, "{} {}", name, value).unwrap();
+        }
+
+        let rpc = self.rpc.lock().unwrap();
+        let rpc_family = |out: &mut String, name: &str, help: &str, value: fn(&RpcStats) -> f64| {
+            write_family(out, name, "counter", help);
+            for (method, stats) in rpc.iter() {
+                writeln!(out, "{}{{method=\"{}\"}} {}", name, method, value(stats)).unwrap();
+            }
+        };
+        rpc_family(&mut out, "ore_rpc_requests_total", "RPC requests made.", |stats| {
+            stats.requests as f64
+        });
+        rpc_family(&mut out, "ore_rpc_errors_total", "RPC requests that failed.", |stats| {
+            stats.errors as f64
+        });
+        rpc_family(&mut out, "ore_rpc_request_seconds_total", "Time spent waiting on RPC requests.", |stats| {
+            stats.seconds
+        });
+        out
+    }
+}
+
+fn per_wallet(values: &Mutex<BTreeMap<Pubkey, f64>>) -> Vec<(Pubkey, f64)> {
+    values.lock().unwrap().iter().map(|(wallet, value)| (*wallet, *value)).collect()
+}
+
+fn write_family(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+    writeln!(out, "# TYPE {} {}", name, kind).unwrap();
+}
+
+/// Serves the metrics at `/metrics` on `addr` in the background.
+pub fn serve(metrics: Arc<Metrics>, addr: SocketAddr) {
+    let make_service = make_service_fn(move |_| {
+        let metrics = metrics.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                let metrics = metrics.clone();
+                async move {
+                    let response = if request.uri().path().eq("/metrics") {
+                        Response::new(Body::from(metrics.render()))
+                    } else {
+                        let mut response = Response::new(Body::empty());
+                        *response.status_mut() = StatusCode::NOT_FOUND;
+                        response
+                    };
+                    Ok::<_, Infallible>(response)
+                }
+            }))
+        }
+    });
+    let server = match Server::try_bind(&addr) {
+        Ok(builder) => builder.serve(make_service),
+        Err(err) => {
+            error!("Error serving metrics on {}: {}", addr, err);
+            return;
+        }
+    };
+    info!("Serving metrics on http://{}/metrics", addr);
+    tokio::spawn(async move {
+        if let Err(err) = server.await {
+            error!("Metrics server stopped: {}", err);
+        }
+    });
+}
+
+/// A chain that times and counts each call into the metrics.
+pub struct MeteredChain<C> {
+    inner: C,
+    metrics: Arc<Metrics>,
+}
+
+impl<C: Chain> MeteredChain<C> {
+    pub fn new(inner: C, metrics: Arc<Metrics>) -> Self {
+        Self { inner, metrics }
+    }
+
+    async fn observe<T>(
+        &self,
+        method: &'static str,
+        call: impl Future<Output = ClientResult<T>>,
+    ) -> ClientResult<T> {
+        let started = Instant::now();
+        let result = call.await;
+        self.metrics.rpc_call(method, started.elapsed(), result.is_ok());
+        result
+    }
+}
+
+#[async_trait]
+impl<C: Chain> Chain for MeteredChain<C> {
+    async fn get_account(&self, address: &Pubkey) -> ClientResult<Option<Account>> {
+        self.observe("getAccountInfo", self.inner.get_account(address)).await
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<Option<Account>>> {
+        self.observe("getMultipleAccounts", self.inner.get_multiple_accounts(addresses))
+            .await
+    }
+
+    async fn get_multiple_accounts_with_commitment(
+        &self,
+        addresses: &[Pubkey],
+        commitment: CommitmentConfig,
+    ) -> ClientResult<Vec<Option<Account>>> {
+        self.observe(
+            "getMultipleAccounts",
+            self.inner
+                .get_multiple_accounts_with_commitment(addresses, commitment),
+        )
+        .await
+    }
+
+    async fn get_slot(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        self.observe("getSlot", self.inner.get_slot(commitment)).await
+    }
+
+    async fn get_balance(&self, address: &Pubkey) -> ClientResult<u64> {
+        self.observe("getBalance", self.inner.get_balance(address)).await
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        self.observe(
+            "getMinimumBalanceForRentExemption",
+            self.inner.get_minimum_balance_for_rent_exemption(data_len),
+        )
+        .await
+    }
+
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        self.observe("getLatestBlockhash", self.inner.get_latest_blockhash())
+            .await
+    }
+
+    async fn send_transaction(&self, tx: &VersionedTransaction) -> ClientResult<Signature> {
+        self.observe("sendTransaction", self.inner.send_transaction(tx))
+            .await
+    }
+
+    async fn simulate_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> ClientResult<Option<TransactionError>> {
+        self.observe("simulateTransaction", self.inner.simulate_transaction(tx))
+            .await
+    }
+
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<Result<(), TransactionError>>> {
+        self.observe("getSignatureStatuses", self.inner.get_signature_status(signature))
+            .await
+    }
+}
@@ -1,149 +1,456 @@
 use std::{
-    io::{stdout, Write}, sync::{atomic::AtomicBool, Arc, Mutex}, vec
+    collections::{HashMap, HashSet}, ops::ControlFlow, str::FromStr, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, time::{Duration, Instant}, vec
 };
 
-use ore::{self, state::Bus, BUS_ADDRESSES, BUS_COUNT, EPOCH_DURATION};
+use ore::{self, state::{Bus, Proof}, BUS_ADDRESSES, BUS_COUNT, EPOCH_DURATION};
 use rand::Rng;
-use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
-    commitment_config::CommitmentConfig,  keccak::{hashv, Hash as KeccakHash}, signature::{Keypair, Signer},
+    keccak::{hashv, Hash as KeccakHash}, native_token::{lamports_to_sol, sol_to_lamports}, pubkey::Pubkey, signature::{Keypair, Signer},
 };
 use futures::future::join_all;
+use jito_protos::bundle::{bundle_result::Result as BundleOutcome, BundleResult};
+use log::{debug, error, info, warn};
 use std::thread;
+use tokio::{sync::mpsc::UnboundedReceiver, time::sleep};
 
 use crate::{
-    Miner,
-    utils::{get_clock_account, get_proof, get_treasury},
+    Miner, MineArgs,
+    bundle::BundleStatus,
+    contention::BusContention,
+    history::{self, Ledger, LedgerEvent},
+    logger, metrics,
+    payer::{is_insufficient_funds, PayerMonitor},
+    profit::ProfitGuard,
+    send_and_confirm::{BundleTransport, SentBundles},
+    sender::SenderKind,
+    tip::TipController,
+    utils::{get_clock_account, get_proof, get_token_account, get_treasury, ore_ui_amount},
 };
 
 // Odds of being selected to submit a reset tx
 const RESET_ODDS: u64 = 20;
 
+// Seconds to wait before re-checking an unprofitable bundle
+const UNPROFITABLE_RETRY_DELAY: u64 = 10;
+
+/// What a mining run carries from one round to the next.
+pub struct MiningSession {
+    threads: u64,
+    dry_run: bool,
+    /// Claim threshold in base units and the token account claims go to.
+    auto_claim: Option<(u64, Pubkey)>,
+    payer_monitor: PayerMonitor,
+    profit_guard: Option<ProfitGuard>,
+    ledger: Option<Ledger>,
+    tip_controller: Option<TipController>,
+    dynamic_tip: bool,
+    min_tip: u64,
+    max_tip: u64,
+    bundle_results: Option<UnboundedReceiver<BundleResult>>,
+    bus_contention: Option<BusContention>,
+    /// Total rewards of each proof when last fetched, to record what each round earned.
+    total_rewards: HashMap<Pubkey, u64>,
+}
+
 impl Miner {
-    pub async fn mine(&self, threads: u64) {
+    pub async fn mine(&self, args: MineArgs) {
+        if let Some(addr) = args.metrics_addr {
+            metrics::serve(self.metrics.clone(), addr);
+        }
+
         // Register, if needed.
-        self.register().await;
-        let mut rng = rand::thread_rng();
+        if self.register().await && self.dry_run {
+            info!("Dry run: mine transactions need the wallets registered first, exiting");
+            return;
+        }
+
+        let mut session = self.start_mining(args).await;
 
         // why tf
         // stdout.write_all(b"\x1b[2J\x1b[3J\x1b[H").ok();
 
         for signer in self.signers() {
-            println!("{}: Starting to Mine", signer.pubkey());
+            info!(wallet:% = signer.pubkey(); "Starting to mine");
         }
 
         // Start mining loop
-        'mining_loop:loop {
-            // Fetch account states for all 5 keypairs
-            let treasury = get_treasury(self.cluster.clone()).await;
-
-            let self_arc = Arc::new(self);
-            let vec_signers = self.signers(); 
+        loop {
+            let vec_signers = self.signers();
             let signers: Vec<&Keypair> = vec_signers.iter().collect(); // Convert
+            if self.mine_round(&mut session, &signers).await.is_break() {
+                return;
+            }
+        }
+    }
 
-            let proofs_futures: Vec<_> = signers.iter()
-                .map(|signer| get_proof(self_arc.cluster.clone(), signer.pubkey()))
-                .collect();
+    /// Sets up what mining keeps across rounds: the auto-claim beneficiary, the payer and
+    /// profit checks, the ledger, and the tip controller with its bundle result subscription.
+    pub async fn start_mining(&self, args: MineArgs) -> MiningSession {
+        let dry_run = self.dry_run;
 
-            let proofs = join_all(proofs_futures).await;
+        // Resolve the auto-claim threshold and beneficiary once up front.
+        let auto_claim = match args.auto_claim {
+            Some(threshold) => {
+                let beneficiary = match args.auto_claim_beneficiary {
+                    Some(beneficiary) => Pubkey::from_str(&beneficiary)
+                        .expect("Failed to parse beneficiary address"),
+                    None => self.initialize_ata().await,
+                };
+                let threshold = (threshold * 10f64.powf(ore::TOKEN_DECIMALS as f64)) as u64;
+                Some((threshold, beneficiary))
+            }
+            None => None,
+        };
+        let payer_monitor = PayerMonitor::new(
+            sol_to_lamports(args.payer_warn),
+            sol_to_lamports(args.payer_min),
+        );
+        let profit_guard = args
+            .profit_guard
+            .then(|| ProfitGuard::new(args.ore_price, args.price_url));
+        // Nothing is sent in a dry run, so there is nothing to record
+        let ledger = (!dry_run).then(|| Ledger::new(args.ledger));
+        // Measure the first round's rewards from the totals the last run recorded
+        let total_rewards = match ledger.as_ref().map(Ledger::read) {
+            Some(Ok(events)) => history::last_total_rewards(&events),
+            Some(Err(err)) => {
+                warn!("Error reading ledger, not recording rewards until the next round: {}", err);
+                HashMap::new()
+            }
+            None => HashMap::new(),
+        };
+        // Bundles sent to the block engine are tracked until they resolve,
+        // and only they pay a tip worth adjusting
+        let tip_controller = (self.sender.eq(&SenderKind::Jito) && !dry_run)
+            .then(|| TipController::new(self.jito_fee, args.min_tip, args.max_tip));
+        let dynamic_tip = args.dynamic_tip && tip_controller.is_some();
+        if args.dynamic_tip && self.sender.ne(&SenderKind::Jito) {
+            warn!("Dynamic tip needs the jito sender, keeping the tip fixed");
+        }
+        // Bundle results are streamed over grpc and polled otherwise
+        let mut bundle_results = None;
+        if tip_controller.is_some() && self.bundle_transport.eq(&BundleTransport::Grpc) {
+            match self.subscribe_bundle_results().await {
+                Ok(receiver) => bundle_results = Some(receiver),
+                Err(err) => {
+                    warn!("Error subscribing to bundle results, polling statuses instead: {}", err)
+                }
+            }
+        }
+        // Bus contention comes from the grpc mempool subscription, where the block engine supports it
+        let mut bus_contention = None;
+        if args.avoid_contention {
+            if self.bundle_transport.eq(&BundleTransport::Grpc) {
+                match self.subscribe_bus_contention().await {
+                    Ok(contention) => bus_contention = Some(contention),
+                    Err(err) => warn!("Error subscribing to the mempool, picking buses at random: {}", err),
+                }
+            } else {
+                warn!("Bus contention needs the grpc transport, picking buses at random");
+            }
+        }
 
-            let proofs_clone = proofs.clone();
+        MiningSession {
+            threads: args.threads,
+            dry_run,
+            auto_claim,
+            payer_monitor,
+            profit_guard,
+            ledger,
+            tip_controller,
+            dynamic_tip,
+            min_tip: args.min_tip,
+            max_tip: args.max_tip,
+            bundle_results,
+            bus_contention,
+            total_rewards,
+        }
+    }
 
-            // Commenting out the parallel processing code
-            
-            let handles: Vec<_> = signers.iter().zip(proofs.into_iter())
-                .map(|(signer, proof)| {
-                    let signer = signer.insecure_clone();
-                    let proof = proof.clone();
-                    let difficulty = treasury.difficulty.into();
-                    thread::spawn(move || find_next_hash_par(signer, proof.hash.into(), difficulty, threads))
-                })
-                .collect();
-
-            let results_with_nonces: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
-            
-
-            // Sequential processing
-            /* let results_with_nonces: Vec<_> = signers.into_iter().zip(proofs.into_iter())
-                .map(|(signer, proof)| {
-                    let signer = signer.insecure_clone();
-                    let proof = proof.clone();
-                    let difficulty = treasury.difficulty.into();
-                    find_next_hash_par(signer, proof.hash.into(), difficulty, threads)
+    /// Hashes every signer's proof once and sends the solutions, retrying the send until it
+    /// goes out. Breaks when mining should stop, as after the one send of a dry run.
+    pub async fn mine_round(
+        &self,
+        session: &mut MiningSession,
+        signers: &[&Keypair],
+    ) -> ControlFlow<()> {
+        let threads = session.threads;
+        let dry_run = session.dry_run;
+        let auto_claim = session.auto_claim;
+        let dynamic_tip = session.dynamic_tip;
+        let ledger = session.ledger.as_ref();
+
+        // Wait for the fee payer to hold enough SOL to keep mining
+        let payer_balance = self.check_payer_balance(&mut session.payer_monitor).await;
+
+        if let Some(profit_guard) = session.profit_guard.as_mut() {
+            profit_guard.refresh_price().await;
+        }
+
+        // Fetch account states for all 5 keypairs
+        let treasury = get_treasury(self.chain.as_ref()).await;
+
+        let proofs_futures: Vec<_> = signers.iter()
+            .map(|signer| get_proof(self.chain.as_ref(), signer.pubkey()))
+            .collect();
+
+        let proofs = join_all(proofs_futures).await;
+
+        let proofs_clone = proofs.clone();
+        for (signer, proof) in signers.iter().zip(proofs.iter()) {
+            self.metrics.set_claimable(signer.pubkey(), proof.claimable_rewards);
+            let previous = session.total_rewards.insert(signer.pubkey(), proof.total_rewards);
+            if let (Some(ledger), Some(previous)) = (ledger, previous) {
+                if proof.total_rewards.gt(&previous) {
+                    ledger.append(LedgerEvent::Reward {
+                        timestamp: history::now(),
+                        wallet: signer.pubkey().to_string(),
+                        delta: proof.total_rewards - previous,
+                    });
+                }
+            }
+        }
+
+        // Commenting out the parallel processing code
+        
+        let handles: Vec<_> = signers.iter().zip(proofs.into_iter())
+            .map(|(signer, proof)| {
+                let signer = signer.insecure_clone();
+                let difficulty = treasury.difficulty.into();
+                let metrics = self.metrics.clone();
+                thread::spawn(move || {
+                    let started = Instant::now();
+                    let pubkey = signer.pubkey();
+                    let (hash, nonce) = find_next_hash_par(signer, proof.hash.into(), difficulty, threads);
+                    metrics.solution_found(pubkey, hashes_tried(nonce, threads), started.elapsed());
+                    (hash, nonce)
                 })
-                .collect(); */
-            let results: Vec<_> = results_with_nonces.iter().map(|(result, _)| result.clone()).collect();
-            let nonces: Vec<_> = results_with_nonces.iter().map(|(_, nonce)| nonce.clone()).collect();
-
-            let reward_rate = (treasury.reward_rate as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
-
-            let rewards: Vec<_> = proofs_clone.into_iter()
-                .map(|proof| (proof.claimable_rewards as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64)))
-                .collect();
-            
-            for signer in self.signers() {
-                println!("Wallet: {}", signer.pubkey());
+            })
+            .collect();
+
+        // Claim rewards over the threshold while the hashing threads run
+        if let Some((threshold, beneficiary)) = auto_claim {
+            self.auto_claim(signers, &proofs_clone, threshold, beneficiary).await;
+        }
+
+        let results_with_nonces: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+        
+
+        // Sequential processing
+        /* let results_with_nonces: Vec<_> = signers.into_iter().zip(proofs.into_iter())
+            .map(|(signer, proof)| {
+                let signer = signer.insecure_clone();
+                let proof = proof.clone();
+                let difficulty = treasury.difficulty.into();
+                find_next_hash_par(signer, proof.hash.into(), difficulty, threads)
+            })
+            .collect(); */
+        let results: Vec<_> = results_with_nonces.iter().map(|(result, _)| *result).collect();
+        let nonces: Vec<_> = results_with_nonces.iter().map(|(_, nonce)| *nonce).collect();
+
+        let reward_rate = (treasury.reward_rate as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
+
+        let rewards: Vec<_> = proofs_clone.iter()
+            .map(|proof| (proof.claimable_rewards as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64)))
+            .collect();
+        
+        for (signer, reward) in signers.iter().zip(rewards) {
+            info!(wallet:% = signer.pubkey(); "Claimable: {} ORE", reward);
+        }
+        info!("Reward rate: {} ORE", reward_rate);
+        info!("Enable JitoTip: {}", self.jito_enable);
+        info!("JitoTip Fee: {}", self.jito_tip());
+        if dynamic_tip {
+            info!("Dynamic tip: {} - {} lamports", session.min_tip, session.max_tip);
+        }
+        if let Some((threshold, beneficiary)) = auto_claim {
+            let thresholdf = (threshold as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
+            info!("Auto-claim: {} ORE to {}", thresholdf, beneficiary);
+        }
+        session.payer_monitor.print_summary(payer_balance);
+
+        info!("Mining for valid hashes...");
+
+        // Wallets whose solutions already went out, so a retry only resends the rest
+        let mut sent_wallets = HashSet::new();
+        loop {
+            // Reset epoch, if needed
+            let treasury = get_treasury(self.chain.as_ref()).await;
+            let clock = get_clock_account(self.chain.as_ref()).await;
+            let threshold = treasury.last_reset_at.saturating_add(EPOCH_DURATION);
+            if clock.unix_timestamp.ge(&threshold) {
+                // There are a lot of miners right now, so randomly select into submitting tx
+                if rand::thread_rng().gen_range(0..RESET_ODDS).eq(&0) {
+                    info!("Sending epoch reset transaction...");
+                    let reset_ixs: Vec<_> = signers.iter().map(|signer| {
+                        ore::instruction::reset(signer.pubkey())
+                    }).collect();
+                    match self.send_and_confirm(&reset_ixs, signers).await {
+                        Ok(uuids) => info!(bundle = uuids.join(","); "Reset bundle sent"),
+                        Err(err) => error!("Reset error: {}", err),
+                    }
+                    if dry_run {
+                        return ControlFlow::Break(());
+                    }
+                }
+                return ControlFlow::Continue(());
+            }
+
+            // Submit mine request.
+            let bus = match session.bus_contention.as_ref() {
+                Some(contention) => match self.find_uncontended_bus(treasury.reward_rate, contention).await {
+                    Some(bus) => bus,
+                    None => self.find_bus_id(treasury.reward_rate).await,
+                },
+                None => self.find_bus_id(treasury.reward_rate).await,
+            };
+            let bus_rewards = (bus.rewards as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
+            info!(bus = bus.id; "Sending on bus {} ({} ORE)", bus.id, bus_rewards);
+
+            let mut mine_ixs = vec![];
+            for (i, signer) in signers.iter().enumerate() {
+                if sent_wallets.contains(&signer.pubkey()) {
+                    continue;
+                }
+                mine_ixs.push(ore::instruction::mine(
+                    signer.pubkey(),
+                    BUS_ADDRESSES[bus.id as usize],
+                    results[i].into(),
+                    nonces[i],
+                ));
+            }
+            let ixs = mine_ixs;
+
+            // Adjust the tip from the results of previous bundles
+            if let Some(tip_controller) = session.tip_controller.as_mut() {
+                match session.bundle_results.as_mut() {
+                    Some(bundle_results) => {
+                        while let Ok(result) = bundle_results.try_recv() {
+                            if let Some((landed, tip)) = tip_controller.observe(&result) {
+                                let slot = processed_slot(&result);
+                                self.bundle_resolved(ledger, &result.bundle_id, landed, slot, tip);
+                            }
+                        }
+                    }
+                    None => self.poll_tip_results(tip_controller, ledger).await,
+                }
+                for uuid in tip_controller.expire() {
+                    self.bundle_resolved(ledger, &uuid, false, None, 0);
+                }
             }
-            for reward in rewards {
-                println!("Claimable: {} ORE", reward);
+            if let Some(tip_controller) = session.tip_controller.as_mut().filter(|_| dynamic_tip) {
+                if let Some(price) = session.profit_guard.as_ref().and_then(|guard| guard.price()) {
+                    let estimate =
+                        self.bundle_estimate(&ixs, ixs.len(), treasury.reward_rate, 0, price);
+                    // Skip rather than pay a tip above what the bundle is worth
+                    if !tip_controller.cap(estimate.max_tip()) {
+                        info!(
+                            "Skipping bundle, no tip from {} lamports is profitable at {} SOL/ORE, retrying in {}s",
+                            session.min_tip, price, UNPROFITABLE_RETRY_DELAY
+                        );
+                        sleep(Duration::from_secs(UNPROFITABLE_RETRY_DELAY)).await;
+                        if let Some(profit_guard) = session.profit_guard.as_mut() {
+                            profit_guard.refresh_price().await;
+                        }
+                        continue;
+                    }
+                }
+                self.jito_tip.store(tip_controller.tip(), Ordering::Relaxed);
+                info!("JitoTip: {}", tip_controller.tip());
             }
-            println!("Reward rate: {} ORE", reward_rate);
-            println!("Enable JitoTip: {}", self.jito_enable);
-            println!("JitoTip Fee: {}", self.jito_fee);
-
-            // Escape sequence that clears the screen and the scrollback buffer
-            println!("\nMining for valid hashes...");
-
-
-            loop {
-                // Reset epoch, if needed
-                let treasury = get_treasury(self.cluster.clone()).await;
-                let clock = get_clock_account(self.cluster.clone()).await;
-                let threshold = treasury.last_reset_at.saturating_add(EPOCH_DURATION);
-                if clock.unix_timestamp.ge(&threshold) {
-                    // There are a lot of miners right now, so randomly select into submitting tx
-                    if rng.gen_range(0..RESET_ODDS).eq(&0) {
-                        println!("Sending epoch reset transaction...");
-                        let reset_ixs: Vec<_> = signers.iter().map(|signer| {
-                            ore::instruction::reset(signer.pubkey())
-                        }).collect();
-                        let uuid = self.send_and_confirm(&reset_ixs, &signers).await.ok();
-                        println!("Bundle sent wth uuid {:?}\n", uuid);
+
+            // Skip the bundle if it would cost more than it earns
+            if let Some(profit_guard) = session.profit_guard.as_mut() {
+                match profit_guard.price() {
+                    Some(price) => {
+                        let tip = if self.sender.pays_tip() { self.jito_tip() } else { 0 };
+                        let estimate = self.bundle_estimate(
+                            &ixs,
+                            ixs.len(),
+                            treasury.reward_rate,
+                            tip,
+                            price,
+                        );
+                        estimate.print();
+                        if !estimate.is_profitable() {
+                            info!(
+                                "Skipping unprofitable bundle at {} SOL/ORE, retrying in {}s",
+                                price, UNPROFITABLE_RETRY_DELAY
+                            );
+                            sleep(Duration::from_secs(UNPROFITABLE_RETRY_DELAY)).await;
+                            // Re-check against the current price, not the one that failed
+                            profit_guard.refresh_price().await;
+                            continue;
+                        }
                     }
-                    continue 'mining_loop
+                    None => warn!("No ORE price available, sending without profit check"),
                 }
+            }
 
-                // Submit mine request.
-                let bus = self.find_bus_id(treasury.reward_rate).await;
-                let bus_rewards = (bus.rewards as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
-                println!("Sending on bus {} ({} ORE)", bus.id, bus_rewards);
-
-                let mut mine_ixs = vec![];
-                for (i, signer) in signers.iter().enumerate() {
-                    mine_ixs.push(ore::instruction::mine(
-                        signer.pubkey(),
-                        BUS_ADDRESSES[bus.id as usize],
-                        results[i].into(),
-                        nonces[i],
-                    ));
+
+            match self
+                .send_bundles(&ixs, signers)
+                .await
+            {
+                Ok(sent) => {
+                    info!(bundle = sent.ids.join(","); "Bundle sent");
+                    // Nothing went out to resolve or record
+                    if dry_run {
+                        return ControlFlow::Break(());
+                    }
+                    self.record_sent(
+                        ledger,
+                        session.tip_controller.as_mut(),
+                        signers,
+                        &proofs_clone,
+                        &results_with_nonces,
+                        &treasury.difficulty.to_string(),
+                        bus.id,
+                        &sent,
+                    );
+                    return ControlFlow::Continue(());
                 }
-                let ixs = mine_ixs;
-
-                match self
-                    .send_and_confirm(&ixs, &signers)
-                    .await
-                {
-                    Ok(sig) => {
-                        println!("Bundle sent with uuid {}\n", sig);
-                        break;
+                Err(_err) => {
+                    error!("send_and_confirm Error: {}", _err);
+                    if dry_run {
+                        return ControlFlow::Break(());
                     }
-                    Err(_err) => {
-                        println!("send_and_confirm Error: {}", _err.to_string());
 
-                        if Miner::should_break_loop(&_err.to_string()) {
-                            continue 'mining_loop;
+                    // Record the bundles that went out before the failure and leave their
+                    // wallets out of the retry, since their proofs have already moved on
+                    if !_err.sent.ids.is_empty() {
+                        self.record_sent(
+                            ledger,
+                            session.tip_controller.as_mut(),
+                            signers,
+                            &proofs_clone,
+                            &results_with_nonces,
+                            &treasury.difficulty.to_string(),
+                            bus.id,
+                            &_err.sent,
+                        );
+                        sent_wallets.extend(_err.sent.signers.iter().copied());
+                        if signers.iter().all(|signer| sent_wallets.contains(&signer.pubkey())) {
+                            return ControlFlow::Continue(());
                         }
                     }
+
+                    if Miner::should_break_loop(&_err.to_string()) {
+                        return ControlFlow::Continue(());
+                    }
+
+                    // Wait for the fee payer to be topped up, then resend the same solutions
+                    if is_insufficient_funds(&_err) {
+                        let payer = self.feepayer().pubkey();
+                        let balance = self.chain.get_balance(&payer).await.unwrap_or_default();
+                        warn!(
+                            wallet:% = payer;
+                            "Fee payer cannot pay for the bundle with {} SOL, waiting for funds to resend it",
+                            lamports_to_sol(balance)
+                        );
+                        let balance = self.wait_for_funding(balance).await;
+                        info!("Fee payer funded with {} SOL, resending", lamports_to_sol(balance));
+                    }
                 }
             }
         }
@@ -161,6 +468,122 @@ impl Miner {
         }
     }
 
+    /// Records what a send put on chain: a ledger round for each wallet in it, and each
+    /// bundle as pending with the tip controller or, sent over rpc, as landed.
+    #[allow(clippy::too_many_arguments)]
+    fn record_sent(
+        &self,
+        ledger: Option<&Ledger>,
+        tip_controller: Option<&mut TipController>,
+        signers: &[&Keypair],
+        proofs: &[Proof],
+        solutions: &[(KeccakHash, u64)],
+        difficulty: &str,
+        bus: u64,
+        sent: &SentBundles,
+    ) {
+        if let Some(ledger) = ledger {
+            self.record_round(ledger, signers, proofs, solutions, difficulty, bus, sent);
+        }
+        match tip_controller {
+            Some(tip_controller) => {
+                for uuid in sent.ids.iter() {
+                    tip_controller.sent(uuid.clone(), self.jito_tip());
+                }
+            }
+            // Transactions sent over rpc have landed once sent, and were counted as they were
+            None => {
+                for uuid in sent.ids.iter() {
+                    record_outcome(ledger, uuid, true, None);
+                }
+            }
+        }
+    }
+
+    /// Appends a round for each wallet whose mine instruction went out in `sent`,
+    /// splitting the tips and fees its transactions pay between them.
+    #[allow(clippy::too_many_arguments)]
+    fn record_round(
+        &self,
+        ledger: &Ledger,
+        signers: &[&Keypair],
+        proofs: &[Proof],
+        solutions: &[(KeccakHash, u64)],
+        difficulty: &str,
+        bus: u64,
+        sent: &SentBundles,
+    ) {
+        let rounds: Vec<_> = signers
+            .iter()
+            .zip(proofs)
+            .zip(solutions)
+            .filter(|((signer, _), _)| sent.signers.contains(&signer.pubkey()))
+            .collect();
+        let wallets = rounds.len();
+        let timestamp = history::now();
+        for (i, ((signer, proof), (hash, nonce))) in rounds.into_iter().enumerate() {
+            ledger.append(LedgerEvent::Round {
+                timestamp,
+                wallet: signer.pubkey().to_string(),
+                proof_hash: KeccakHash::from(proof.hash).to_string(),
+                total_rewards: Some(proof.total_rewards),
+                hash: hash.to_string(),
+                nonce: *nonce,
+                difficulty: difficulty.to_string(),
+                bus,
+                bundles: sent.ids.clone(),
+                tip: history::share(sent.cost.tip, wallets, i),
+                fee: history::share(sent.cost.fees, wallets, i),
+            });
+        }
+    }
+
+    /// Counts the final result of a bundle sent with a tip of `tip` lamports
+    /// and records it in the ledger.
+    fn bundle_resolved(
+        &self,
+        ledger: Option<&Ledger>,
+        uuid: &str,
+        landed: bool,
+        slot: Option<u64>,
+        tip: u64,
+    ) {
+        self.metrics.bundle_resolved(landed, tip);
+        record_outcome(ledger, uuid, landed, slot);
+    }
+
+    /// Resolves the tip controller's pending bundles from their polled statuses.
+    async fn poll_tip_results(&self, tip_controller: &mut TipController, ledger: Option<&Ledger>) {
+        let uuids = tip_controller.pending();
+        if uuids.is_empty() {
+            return;
+        }
+        let statuses = match self.get_bundle_statuses(&uuids).await {
+            Ok(statuses) => statuses,
+            Err(err) => {
+                warn!("Error polling bundle statuses: {}", err);
+                return;
+            }
+        };
+        for (uuid, status) in uuids.iter().zip(statuses) {
+            match status {
+                BundleStatus::Landed { slot, signatures } => {
+                    info!(bundle = uuid.as_str(), slot = slot; "Bundle landed: {}", signatures.join(", "));
+                    if let Some(tip) = tip_controller.resolve(uuid, true) {
+                        self.bundle_resolved(ledger, uuid, true, Some(slot), tip);
+                    }
+                }
+                BundleStatus::Failed => {
+                    warn!(bundle = uuid.as_str(); "Bundle failed to land");
+                    if let Some(tip) = tip_controller.resolve(uuid, false) {
+                        self.bundle_resolved(ledger, uuid, false, None, tip);
+                    }
+                }
+                BundleStatus::Pending | BundleStatus::TimedOut => {}
+            }
+        }
+    }
+
     fn _find_next_hash(signer: Keypair, hash: KeccakHash, difficulty: KeccakHash) -> (KeccakHash, u64) {
         let mut next_hash: KeccakHash;
         let mut nonce = 0u64;
@@ -173,7 +596,7 @@ impl Miner {
             if next_hash.le(&difficulty) {
                 break;
             } else {
-                println!("Invalid hash: {} Nonce: {:?}", next_hash.to_string(), nonce);
+                debug!("Invalid hash: {} Nonce: {:?}", next_hash, nonce);
             }
             nonce += 1;
         }
@@ -182,17 +605,16 @@ impl Miner {
 
     
 
+    #[allow(dead_code)]
     pub async fn get_ore_display_balance(&self, signer: Keypair) -> String {
-        let client =
-            RpcClient::new_with_commitment(self.cluster.clone(), CommitmentConfig::processed());
         let token_account_address = spl_associated_token_account::get_associated_token_address(
             &signer.pubkey(),
             &ore::MINT_ADDRESS,
         );
-        match client.get_token_account(&token_account_address).await {
+        match get_token_account(self.chain.as_ref(), &token_account_address).await {
             Ok(token_account) => {
                 if let Some(token_account) = token_account {
-                    token_account.token_amount.ui_amount_string
+                    ore_ui_amount(token_account.amount)
                 } else {
                     "0.00".to_string()
                 }
@@ -205,7 +627,32 @@ impl Miner {
     }
 }
 
-fn find_next_hash_par(
+/// Appends the final result of a sent bundle or transaction to the ledger, if there is one.
+fn record_outcome(ledger: Option<&Ledger>, uuid: &str, landed: bool, slot: Option<u64>) {
+    if let Some(ledger) = ledger {
+        ledger.append(LedgerEvent::Outcome {
+            timestamp: history::now(),
+            bundle: uuid.to_string(),
+            landed,
+            slot,
+        });
+    }
+}
+
+/// The slot a streamed bundle result landed in, if it says.
+fn processed_slot(result: &BundleResult) -> Option<u64> {
+    match result.result.as_ref() {
+        Some(BundleOutcome::Processed(processed)) => Some(processed.slot),
+        _ => None,
+    }
+}
+
+/// Hashes tried across all threads to find `nonce`, from where its thread started.
+fn hashes_tried(nonce: u64, threads: u64) -> u64 {
+    (nonce % u64::MAX.saturating_div(threads)).saturating_mul(threads)
+}
+
+pub fn find_next_hash_par(
     signer: Keypair,
     hash: KeccakHash,
     difficulty: KeccakHash,
@@ -217,12 +664,12 @@ fn find_next_hash_par(
         0,
     )));
     let pubkey = signer.pubkey();
+    let started = Instant::now();
     let thread_handles: Vec<_> = (0..threads)
         .map(|i| {
             std::thread::spawn({
                 let found_solution = found_solution.clone();
                 let solution = solution.clone();
-                let mut stdout = stdout();
                 move || {
                     let n = u64::MAX.saturating_div(threads).saturating_mul(i);
                     let mut next_hash: KeccakHash;
@@ -237,18 +684,18 @@ fn find_next_hash_par(
                             if found_solution.load(std::sync::atomic::Ordering::Relaxed) {
                                 return;
                             }
+                            // The first thread reports progress for all of them
                             if n == 0 {
-                                stdout
-                                    .write_all(
-                                        format!("\r{}", next_hash.to_string()).as_bytes(),
-                                    )
-                                    .ok();
+                                let hashes = nonce.saturating_mul(threads);
+                                let rate = hashes as f64 / started.elapsed().as_secs_f64().max(1e-3);
+                                logger::status(&format!(
+                                    "Hashing {}: {} hashes at {:.0} H/s",
+                                    pubkey, hashes, rate
+                                ));
                             }
                         }
                         if next_hash.le(&difficulty) {
-                            stdout
-                                .write_all(format!("\r{}", next_hash.to_string()).as_bytes())
-                                .ok();
+                            debug!(wallet:% = pubkey; "Found hash {} with nonce {}", next_hash, nonce);
                             found_solution.store(true, std::sync::atomic::Ordering::Relaxed);
                             let mut w_solution = solution.lock().expect("failed to lock mutex");
                             *w_solution = (next_hash, nonce);
@@ -0,0 +1,365 @@
+//! An in-process block engine for exercising the auth and bundle paths offline.
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use futures::{
+    channel::mpsc::{unbounded, UnboundedSender},
+    stream, Stream, StreamExt,
+};
+use jito_protos::{
+    auth::{
+        auth_service_server::{AuthService, AuthServiceServer},
+        GenerateAuthChallengeRequest, GenerateAuthChallengeResponse, GenerateAuthTokensRequest,
+        GenerateAuthTokensResponse, RefreshAccessTokenRequest, RefreshAccessTokenResponse, Token,
+    },
+    bundle::{bundle_result::Result as BundleOutcome, BundleResult},
+    convert::versioned_tx_from_packet,
+    searcher::{
+        searcher_service_server::{SearcherService, SearcherServiceServer},
+        ConnectedLeadersRegionedRequest, ConnectedLeadersRegionedResponse, ConnectedLeadersRequest,
+        ConnectedLeadersResponse, GetRegionsRequest, GetRegionsResponse, GetTipAccountsRequest,
+        GetTipAccountsResponse, MempoolSubscription, NextScheduledLeaderRequest,
+        NextScheduledLeaderResponse, PendingTxNotification, SendBundleRequest, SendBundleResponse,
+        SubscribeBundleResultsRequest,
+    },
+};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body,
+};
+use prost_types::Timestamp;
+use serde_json::{json, Value};
+use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
+use tokio::net::TcpListener;
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::send_and_confirm::JITO_TIP_ACCOUNT;
+
+// Lifetime of refresh tokens, long enough that clients never re-run the full auth flow
+const REFRESH_TOKEN_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Lifetime of refreshed access tokens, long enough that clients stop refreshing
+const REFRESHED_ACCESS_TOKEN_TTL: Duration = Duration::from_secs(60 * 60);
+
+type ResultStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// A bundle as the mock block engine received it.
+pub struct ReceivedBundle {
+    pub uuid: String,
+    pub transactions: Vec<VersionedTransaction>,
+    /// The access token the bundle was sent with.
+    pub access_token: String,
+}
+
+#[derive(Default)]
+struct State {
+    /// Challenges issued, by the pubkey they were issued to.
+    challenges: HashMap<Vec<u8>, String>,
+    /// Access tokens that authorize searcher requests.
+    access_tokens: Vec<String>,
+    refresh_tokens: Vec<String>,
+    refreshes: usize,
+    bundles: Vec<ReceivedBundle>,
+    /// Outcomes to stream for the next bundles sent, in order.
+    outcomes: Vec<BundleOutcome>,
+    subscribers: Vec<UnboundedSender<Result<BundleResult, Status>>>,
+}
+
+/// Issues tokens, records bundles and streams scripted results, like a block engine would.
+#[derive(Clone)]
+pub struct MockBlockEngine {
+    state: Arc<Mutex<State>>,
+    /// Lifetime of the access tokens issued by the full auth flow.
+    access_token_ttl: Duration,
+}
+
+impl MockBlockEngine {
+    /// Serves the auth and searcher services on a local port, returning the url to reach them.
+    pub async fn start(access_token_ttl: Duration) -> (Self, String) {
+        let engine = Self {
+            state: Arc::new(Mutex::new(State::default())),
+            access_token_ttl,
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = stream::unfold(listener, |listener| async {
+            let stream = listener.accept().await.map(|(stream, _)| stream);
+            Some((stream, listener))
+        });
+        let server = Server::builder()
+            .add_service(AuthServiceServer::new(engine.clone()))
+            .add_service(SearcherServiceServer::new(engine.clone()))
+            .serve_with_incoming(incoming);
+        tokio::spawn(server);
+        (engine, url)
+    }
+
+    /// Streams `outcomes` as the results of the next bundles sent, one each.
+    pub fn script(&self, outcomes: Vec<BundleOutcome>) {
+        self.state.lock().unwrap().outcomes.extend(outcomes);
+    }
+
+    pub fn bundles(&self) -> Vec<ReceivedBundle> {
+        std::mem::take(&mut self.state.lock().unwrap().bundles)
+    }
+
+    /// Number of times an access token was refreshed.
+    pub fn refreshes(&self) -> usize {
+        self.state.lock().unwrap().refreshes
+    }
+
+    /// The most recently issued access token.
+    pub fn latest_access_token(&self) -> Option<String> {
+        self.state.lock().unwrap().access_tokens.last().cloned()
+    }
+
+    fn authorize<T>(&self, request: &Request<T>) -> Result<String, Status> {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("missing access token"))?;
+        match self.state.lock().unwrap().access_tokens.contains(&token.to_string()) {
+            true => Ok(token.to_string()),
+            false => Err(Status::unauthenticated("invalid access token")),
+        }
+    }
+}
+
+fn token(value: String, ttl: Duration) -> Token {
+    Token {
+        value,
+        expires_at_utc: Some(Timestamp::from(SystemTime::now() + ttl)),
+    }
+}
+
+#[tonic::async_trait]
+impl AuthService for MockBlockEngine {
+    async fn generate_auth_challenge(
+        &self,
+        request: Request<GenerateAuthChallengeRequest>,
+    ) -> Result<Response<GenerateAuthChallengeResponse>, Status> {
+        let mut state = self.state.lock().unwrap();
+        let challenge = format!("challenge-{}", state.challenges.len());
+        state
+            .challenges
+            .insert(request.into_inner().pubkey, challenge.clone());
+        Ok(Response::new(GenerateAuthChallengeResponse { challenge }))
+    }
+
+    async fn generate_auth_tokens(
+        &self,
+        request: Request<GenerateAuthTokensRequest>,
+    ) -> Result<Response<GenerateAuthTokensResponse>, Status> {
+        let request = request.into_inner();
+        let pubkey = Pubkey::try_from(request.client_pubkey.as_slice())
+            .map_err(|_| Status::invalid_argument("invalid pubkey"))?;
+        let signature = Signature::try_from(request.signed_challenge.as_slice())
+            .map_err(|_| Status::invalid_argument("invalid signature"))?;
+
+        // The client signs "<pubkey>-<challenge>" with the keypair it authenticates as
+        let mut state = self.state.lock().unwrap();
+        let issued = state
+            .challenges
+            .remove(pubkey.as_ref())
+            .ok_or_else(|| Status::permission_denied("no challenge issued"))?;
+        if request.challenge.ne(&format!("{}-{}", pubkey, issued))
+            || !signature.verify(pubkey.as_ref(), request.challenge.as_bytes())
+        {
+            return Err(Status::permission_denied("invalid challenge signature"));
+        }
+
+        let access_token = format!("access-{}", state.access_tokens.len());
+        let refresh_token = format!("refresh-{}", state.refresh_tokens.len());
+        state.access_tokens.push(access_token.clone());
+        state.refresh_tokens.push(refresh_token.clone());
+        Ok(Response::new(GenerateAuthTokensResponse {
+            access_token: Some(token(access_token, self.access_token_ttl)),
+            refresh_token: Some(token(refresh_token, REFRESH_TOKEN_TTL)),
+        }))
+    }
+
+    async fn refresh_access_token(
+        &self,
+        request: Request<RefreshAccessTokenRequest>,
+    ) -> Result<Response<RefreshAccessTokenResponse>, Status> {
+        let mut state = self.state.lock().unwrap();
+        if !state.refresh_tokens.contains(&request.into_inner().refresh_token) {
+            return Err(Status::unauthenticated("invalid refresh token"));
+        }
+        let access_token = format!("access-{}", state.access_tokens.len());
+        state.access_tokens.push(access_token.clone());
+        state.refreshes += 1;
+        Ok(Response::new(RefreshAccessTokenResponse {
+            access_token: Some(token(access_token, REFRESHED_ACCESS_TOKEN_TTL)),
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl SearcherService for MockBlockEngine {
+    type SubscribeBundleResultsStream = ResultStream<BundleResult>;
+    type SubscribeMempoolStream = ResultStream<PendingTxNotification>;
+
+    async fn subscribe_bundle_results(
+        &self,
+        request: Request<SubscribeBundleResultsRequest>,
+    ) -> Result<Response<Self::SubscribeBundleResultsStream>, Status> {
+        self.authorize(&request)?;
+        let (sender, receiver) = unbounded();
+        self.state.lock().unwrap().subscribers.push(sender);
+        Ok(Response::new(receiver.boxed()))
+    }
+
+    async fn subscribe_mempool(
+        &self,
+        request: Request<MempoolSubscription>,
+    ) -> Result<Response<Self::SubscribeMempoolStream>, Status> {
+        self.authorize(&request)?;
+        Ok(Response::new(stream::pending().boxed()))
+    }
+
+    async fn send_bundle(
+        &self,
+        request: Request<SendBundleRequest>,
+    ) -> Result<Response<SendBundleResponse>, Status> {
+        let access_token = self.authorize(&request)?;
+        let packets = request
+            .into_inner()
+            .bundle
+            .ok_or_else(|| Status::invalid_argument("missing bundle"))?
+            .packets;
+        let transactions: Vec<VersionedTransaction> =
+            packets.iter().filter_map(versioned_tx_from_packet).collect();
+        if transactions.len().ne(&packets.len()) {
+            return Err(Status::invalid_argument("invalid transaction"));
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let uuid = format!("bundle-{}", state.bundles.len());
+        if !state.outcomes.is_empty() {
+            let result = BundleResult {
+                bundle_id: uuid.clone(),
+                result: Some(state.outcomes.remove(0)),
+            };
+            state
+                .subscribers
+                .retain(|subscriber| subscriber.unbounded_send(Ok(result.clone())).is_ok());
+        }
+        state.bundles.push(ReceivedBundle {
+            uuid: uuid.clone(),
+            transactions,
+            access_token,
+        });
+        Ok(Response::new(SendBundleResponse { uuid }))
+    }
+
+    async fn get_next_scheduled_leader(
+        &self,
+        _request: Request<NextScheduledLeaderRequest>,
+    ) -> Result<Response<NextScheduledLeaderResponse>, Status> {
+        Err(Status::unimplemented("not mocked"))
+    }
+
+    async fn get_connected_leaders(
+        &self,
+        _request: Request<ConnectedLeadersRequest>,
+    ) -> Result<Response<ConnectedLeadersResponse>, Status> {
+        Err(Status::unimplemented("not mocked"))
+    }
+
+    async fn get_connected_leaders_regioned(
+        &self,
+        _request: Request<ConnectedLeadersRegionedRequest>,
+    ) -> Result<Response<ConnectedLeadersRegionedResponse>, Status> {
+        Err(Status::unimplemented("not mocked"))
+    }
+
+    async fn get_tip_accounts(
+        &self,
+        request: Request<GetTipAccountsRequest>,
+    ) -> Result<Response<GetTipAccountsResponse>, Status> {
+        self.authorize(&request)?;
+        Ok(Response::new(GetTipAccountsResponse {
+            accounts: vec![JITO_TIP_ACCOUNT.to_string()],
+        }))
+    }
+
+    async fn get_regions(
+        &self,
+        _request: Request<GetRegionsRequest>,
+    ) -> Result<Response<GetRegionsResponse>, Status> {
+        Err(Status::unimplemented("not mocked"))
+    }
+}
+
+/// Answers the block engine's JSON-RPC bundle API with a canned result per method,
+/// recording each request.
+#[derive(Clone)]
+pub struct MockBundleApi {
+    results: Arc<HashMap<String, Value>>,
+    requests: Arc<Mutex<Vec<Value>>>,
+}
+
+impl MockBundleApi {
+    /// Serves `results`, by method name, on a local port, returning the url to reach them.
+    pub async fn start(results: Vec<(&str, Value)>) -> (Self, String) {
+        let api = Self {
+            results: Arc::new(
+                results
+                    .into_iter()
+                    .map(|(method, result)| (method.to_string(), result))
+                    .collect(),
+            ),
+            requests: Arc::new(Mutex::new(Vec::new())),
+        };
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handler = api.clone();
+        let make_service = make_service_fn(move |_| {
+            let api = handler.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: hyper::Request<Body>| {
+                    let api = api.clone();
+                    async move {
+                        let body = hyper::body::to_bytes(request.into_body()).await?;
+                        let request = serde_json::from_slice(&body).unwrap_or_default();
+                        let response = api.respond(request).to_string();
+                        Ok::<_, hyper::Error>(hyper::Response::new(Body::from(response)))
+                    }
+                }))
+            }
+        });
+        tokio::spawn(hyper::Server::from_tcp(listener).unwrap().serve(make_service));
+        (api, url)
+    }
+
+    /// JSON-RPC requests received so far, in order.
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn respond(&self, request: Value) -> Value {
+        let id = request["id"].clone();
+        let result = request["method"]
+            .as_str()
+            .and_then(|method| self.results.get(method))
+            .cloned();
+        self.requests.lock().unwrap().push(request);
+        match result {
+            Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": "Method not found" },
+            }),
+        }
+    }
+}
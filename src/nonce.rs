@@ -0,0 +1,182 @@
+use log::{error, info};
+use ore::BUS_ADDRESSES;
+use solana_client::nonce_utils::data_from_account;
+use solana_program::{
+    hash::Hash,
+    instruction::Instruction,
+    keccak::Hash as KeccakHash,
+    native_token::lamports_to_sol,
+    pubkey::Pubkey,
+    system_instruction::{advance_nonce_account, create_nonce_account_with_seed},
+    system_program,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    nonce::State,
+    signature::{Keypair, Signer},
+};
+
+use crate::{
+    utils::{get_multiple_accounts_batched, MAX_MULTIPLE_ACCOUNTS},
+    Miner,
+};
+
+// Prefix of the seeds nonce accounts are derived from the fee payer with
+const NONCE_SEED: &str = "ore-nonce";
+
+/// Address of the fee payer's nonce account for the transaction at `index`.
+pub fn nonce_pubkey(authority: &Pubkey, index: usize) -> Pubkey {
+    Pubkey::create_with_seed(authority, &nonce_seed(index), &system_program::ID)
+        .expect("Failed to derive nonce account")
+}
+
+fn nonce_seed(index: usize) -> String {
+    format!("{}-{}", NONCE_SEED, index)
+}
+
+/// A nonce account to sign a transaction with instead of a recent blockhash.
+pub struct DurableNonce {
+    pub address: Pubkey,
+    pub blockhash: Hash,
+}
+
+impl DurableNonce {
+    /// The instruction that must come first in a transaction using this nonce.
+    pub fn advance_ix(&self, authority: &Pubkey) -> Instruction {
+        advance_nonce_account(&self.address, authority)
+    }
+}
+
+impl Miner {
+    pub async fn nonce_init(&self, count: Option<usize>) {
+        if self.durable_nonce {
+            error!("Nonce accounts must be created without --durable-nonce, returning.");
+            return;
+        }
+        let count = match count {
+            Some(count) => count,
+            None => {
+                let signers = self.signers();
+                let signers: Vec<&Keypair> = signers.iter().collect();
+                match self.nonces_needed(&signers).await {
+                    Ok(count) => count,
+                    Err(err) => {
+                        error!("Error counting the transactions of a mining round: {}", err);
+                        return;
+                    }
+                }
+            }
+        };
+        let feepayer = self.feepayer();
+        let lamports = match self
+            .chain
+            .get_minimum_balance_for_rent_exemption(State::size())
+            .await
+        {
+            Ok(lamports) => lamports,
+            Err(err) => {
+                error!("Error fetching the nonce account rent: {}", err);
+                return;
+            }
+        };
+
+        // Only create the nonce accounts that do not exist yet
+        let addresses: Vec<Pubkey> = (0..count)
+            .map(|index| nonce_pubkey(&feepayer.pubkey(), index))
+            .collect();
+        let accounts = match get_multiple_accounts_batched(self.chain.as_ref(), &addresses).await {
+            Ok(accounts) => accounts,
+            Err(err) => {
+                error!("Error fetching nonce accounts: {}", err);
+                return;
+            }
+        };
+        let mut ixs = Vec::new();
+        for (index, (address, account)) in addresses.iter().zip(accounts).enumerate() {
+            if account.is_some() {
+                info!(nonce:% = address; "Already exists");
+                continue;
+            }
+            info!(nonce:% = address; "Will be created");
+            ixs.extend(create_nonce_account_with_seed(
+                &feepayer.pubkey(),
+                address,
+                &feepayer.pubkey(),
+                &nonce_seed(index),
+                &feepayer.pubkey(),
+                lamports,
+            ));
+        }
+        if ixs.is_empty() {
+            info!("No new nonce accounts to create, returning.");
+            return;
+        }
+
+        info!(
+            "Creating {} nonce accounts for {} SOL of rent...",
+            ixs.len() / 2,
+            lamports_to_sol(lamports * (ixs.len() / 2) as u64)
+        );
+        match self.send_and_confirm(&ixs, &[]).await {
+            Ok(uuids) => info!(bundle = uuids.join(","); "Nonce bundle sent"),
+            Err(err) => error!("Error creating nonce accounts: {}", err),
+        }
+    }
+
+    /// How many nonce accounts a mining round for `signers` uses: one per transaction their
+    /// mine instructions pack into with durable nonces, the tip transactions included.
+    pub async fn nonces_needed(
+        &self,
+        signers: &[&Keypair],
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let ixs: Vec<Instruction> = signers
+            .iter()
+            .map(|signer| {
+                ore::instruction::mine(
+                    signer.pubkey(),
+                    BUS_ADDRESSES[0],
+                    KeccakHash::default().into(),
+                    0,
+                )
+            })
+            .collect();
+        let lookup_tables = self.get_lookup_tables().await?;
+        let feepayer = self.feepayer().pubkey();
+        let bundles =
+            self.pack_bundles_with(&ixs, &feepayer, &lookup_tables, Hash::default(), true)?;
+        Ok(bundles.iter().map(Vec::len).sum())
+    }
+
+    /// Fetches the current values of the first `count` nonce accounts of the fee payer.
+    /// Nonces are read at confirmed commitment, so they are not advanced on a dropped fork.
+    pub async fn get_durable_nonces(
+        &self,
+        count: usize,
+    ) -> Result<Vec<DurableNonce>, Box<dyn std::error::Error>> {
+        let authority = self.feepayer().pubkey();
+        let addresses: Vec<Pubkey> = (0..count)
+            .map(|index| nonce_pubkey(&authority, index))
+            .collect();
+        let mut nonces = Vec::with_capacity(count);
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let accounts = self
+                .chain
+                .get_multiple_accounts_with_commitment(chunk, CommitmentConfig::confirmed())
+                .await?;
+            for (address, account) in chunk.iter().zip(accounts) {
+                let Some(account) = account else {
+                    return Err(format!(
+                        "nonce account {} not found, run `ore nonce init --count {}`",
+                        address, count
+                    )
+                    .into());
+                };
+                nonces.push(DurableNonce {
+                    address: *address,
+                    blockhash: data_from_account(&account)?.blockhash(),
+                });
+            }
+        }
+        Ok(nonces)
+    }
+}
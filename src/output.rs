@@ -0,0 +1,139 @@
+use std::fmt;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::utils::ore_ui_amount;
+
+/// How query commands print their results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Short human readable lines.
+    Text,
+    /// Aligned columns under a header row.
+    Table,
+    /// The table's header and rows as comma separated values.
+    Csv,
+    /// One pretty-printed JSON document, for scripts.
+    Json,
+}
+
+/// The result of a query command, printable in every output format.
+pub trait QueryOutput: Serialize {
+    /// Lines printed for `--output text`.
+    fn text(&self) -> Vec<String>;
+
+    /// Header and rows printed for `--output table`.
+    fn table(&self) -> (Vec<&'static str>, Vec<Vec<String>>);
+
+    /// Document printed for `--output csv`, the table's rows unless overridden.
+    fn csv(&self) -> String {
+        let (header, rows) = self.table();
+        render_csv(&header, &rows)
+    }
+}
+
+/// An amount of ORE in base units, with its exact decimal form for reading.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OreAmount {
+    pub amount: u64,
+    /// The amount in ORE, e.g. "1.5".
+    pub ui_amount: String,
+}
+
+impl OreAmount {
+    pub fn new(amount: u64) -> Self {
+        Self {
+            amount,
+            ui_amount: ore_ui_amount(amount),
+        }
+    }
+}
+
+impl Default for OreAmount {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl fmt::Display for OreAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.ui_amount)
+    }
+}
+
+pub fn print<T: QueryOutput>(output: &T, format: OutputFormat) {
+    match format {
+        OutputFormat::Text => {
+            for line in output.text() {
+                println!("{}", line);
+            }
+        }
+        OutputFormat::Table => {
+            let (header, rows) = output.table();
+            print!("{}", render_table(&header, &rows));
+        }
+        OutputFormat::Csv => print!("{}", output.csv()),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(output).expect("Failed to serialize output")
+        ),
+    }
+}
+
+/// Reports a failed query on stderr and exits non-zero, leaving stdout to results.
+pub fn fail(message: impl fmt::Display) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(1);
+}
+
+/// Rows padded into columns as wide as their widest cell.
+pub fn render_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|title| title.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let header: Vec<String> = header.iter().map(|title| title.to_string()).collect();
+    let mut out = String::new();
+    for row in [&header].into_iter().chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Header and rows as CSV, quoting cells that need it.
+pub fn render_csv(header: &[&str], rows: &[Vec<String>]) -> String {
+    let header: Vec<String> = header.iter().map(|title| title.to_string()).collect();
+    let mut out = String::new();
+    for row in [&header].into_iter().chain(rows) {
+        let cells: Vec<String> = row.iter().map(|cell| csv_cell(cell)).collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+/// Key and value rows of a single record, for its table output.
+pub fn fields(fields: &[(&str, String)]) -> (Vec<&'static str>, Vec<Vec<String>>) {
+    let rows = fields
+        .iter()
+        .map(|(name, value)| vec![name.to_string(), value.clone()])
+        .collect();
+    (vec!["FIELD", "VALUE"], rows)
+}
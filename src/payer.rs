@@ -0,0 +1,165 @@
+use std::{collections::VecDeque, error::Error, time::Duration};
+
+use log::{error, info, warn};
+use solana_client::client_error::ClientError;
+use solana_program::{instruction::InstructionError, native_token::lamports_to_sol};
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+use tokio::time::sleep;
+
+use crate::Miner;
+
+// Number of recent rounds to average the fee payer's spend over
+const SPEND_WINDOW: usize = 10;
+
+// Seconds to wait between balance checks while mining is paused
+const PAUSE_INTERVAL: u64 = 30;
+
+/// Tracks the fee payer's balance across mining rounds to estimate how long it will last.
+pub struct PayerMonitor {
+    /// Balance below which a warning is printed each round.
+    warn_at: u64,
+    /// Balance below which mining pauses until the payer is topped up.
+    min_balance: u64,
+    /// Balance observed in the previous round.
+    last_balance: Option<u64>,
+    /// Lamports spent in each of the most recent rounds.
+    spends: VecDeque<u64>,
+}
+
+impl PayerMonitor {
+    pub fn new(warn_at: u64, min_balance: u64) -> Self {
+        Self {
+            warn_at,
+            min_balance,
+            last_balance: None,
+            spends: VecDeque::with_capacity(SPEND_WINDOW),
+        }
+    }
+
+    /// Records the balance observed at the start of a round.
+    pub fn record(&mut self, balance: u64) {
+        // A higher balance means the payer was topped up, which is not spend
+        if let Some(last_balance) = self.last_balance {
+            if balance.le(&last_balance) {
+                if self.spends.len().eq(&SPEND_WINDOW) {
+                    self.spends.pop_front();
+                }
+                self.spends.push_back(last_balance - balance);
+            }
+        }
+        self.last_balance = Some(balance);
+    }
+
+    /// Average lamports spent per round over the recent window.
+    pub fn average_spend(&self) -> Option<u64> {
+        if self.spends.is_empty() {
+            return None;
+        }
+        Some(self.spends.iter().sum::<u64>() / self.spends.len() as u64)
+    }
+
+    /// Estimated number of rounds the balance can pay for at the recent rate of spend.
+    pub fn rounds_remaining(&self, balance: u64) -> Option<u64> {
+        match self.average_spend() {
+            Some(spend) if spend.gt(&0) => Some(balance.saturating_sub(self.min_balance) / spend),
+            _ => None,
+        }
+    }
+
+    pub fn print_summary(&self, balance: u64) {
+        let rounds = match self.rounds_remaining(balance) {
+            Some(rounds) => rounds.to_string(),
+            None => "unknown".to_string(),
+        };
+        let spend = self.average_spend().unwrap_or(0);
+        info!(
+            "Fee payer: {} SOL (~{} SOL/round, ~{} rounds remaining)",
+            lamports_to_sol(balance),
+            lamports_to_sol(spend),
+            rounds
+        );
+        if balance.lt(&self.warn_at) {
+            warn!(
+                "Fee payer balance is below {} SOL, top it up soon",
+                lamports_to_sol(self.warn_at)
+            );
+        }
+    }
+}
+
+/// Whether a send failed because the fee payer could not pay for it, from the transaction
+/// error anywhere in its chain of sources.
+pub fn is_insufficient_funds(err: &(dyn Error + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(err) = transaction_error(err) {
+            return matches!(
+                err,
+                TransactionError::InsufficientFundsForFee
+                    | TransactionError::InsufficientFundsForRent { .. }
+                    // A fee payer that was never funded has no account at all
+                    | TransactionError::AccountNotFound
+                    | TransactionError::InstructionError(_, InstructionError::InsufficientFunds)
+            );
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// The transaction error a send failed with, sent or simulated, if this error carries one.
+fn transaction_error(err: &(dyn Error + 'static)) -> Option<TransactionError> {
+    if let Some(err) = err.downcast_ref::<TransactionError>() {
+        return Some(err.clone());
+    }
+    err.downcast_ref::<ClientError>()
+        .and_then(ClientError::get_transaction_error)
+}
+
+impl Miner {
+    /// Fetches the fee payer's balance, pausing until it is at or above the monitor's minimum.
+    pub async fn check_payer_balance(&self, monitor: &mut PayerMonitor) -> u64 {
+        let pubkey = self.feepayer().pubkey();
+        loop {
+            let balance = self.chain.get_balance(&pubkey).await;
+            if let Ok(balance) = balance {
+                self.metrics.set_fee_payer_balance(balance);
+            }
+            match balance {
+                Ok(balance) if balance.ge(&monitor.min_balance) => {
+                    monitor.record(balance);
+                    return balance;
+                }
+                Ok(balance) => {
+                    warn!(
+                        wallet:% = pubkey;
+                        "Fee payer has {} SOL, below the minimum of {} SOL. Pausing mining...",
+                        lamports_to_sol(balance),
+                        lamports_to_sol(monitor.min_balance)
+                    );
+                }
+                Err(err) => {
+                    error!("Error fetching fee payer balance: {}", err);
+                }
+            }
+            sleep(Duration::from_secs(PAUSE_INTERVAL)).await;
+        }
+    }
+
+    /// Waits until the fee payer holds more than `balance`, the balance a send just failed
+    /// to pay from, returning the new balance.
+    pub async fn wait_for_funding(&self, balance: u64) -> u64 {
+        let pubkey = self.feepayer().pubkey();
+        loop {
+            match self.chain.get_balance(&pubkey).await {
+                Ok(funded) if funded.gt(&balance) => {
+                    self.metrics.set_fee_payer_balance(funded);
+                    return funded;
+                }
+                Ok(_) => {}
+                Err(err) => error!("Error fetching fee payer balance: {}", err),
+            }
+            sleep(Duration::from_secs(PAUSE_INTERVAL)).await;
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use log::{info, warn};
+use solana_program::{
+    hash::Hash,
+    instruction::Instruction,
+    native_token::{lamports_to_sol, sol_to_lamports},
+};
+use solana_sdk::signature::Signer;
+
+use crate::{
+    cu_limits::CU_LIMIT_TRANSACTION,
+    dry_run::{compute_unit_limit, priority_fee},
+    send_and_confirm::MAX_BUNDLE_TXS,
+    Miner,
+};
+
+// Base fee the runtime charges for each transaction signature
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Skips mine bundles that would cost more SOL than the ORE they earn is worth.
+pub struct ProfitGuard {
+    /// Price of one ORE in SOL set on the command line, if any.
+    configured_price: Option<f64>,
+    /// Endpoint to fetch the price from when none is configured.
+    price_url: String,
+    /// Most recently fetched price of one ORE in SOL.
+    fetched_price: Option<f64>,
+}
+
+impl ProfitGuard {
+    pub fn new(configured_price: Option<f64>, price_url: String) -> Self {
+        Self {
+            configured_price,
+            price_url,
+            fetched_price: None,
+        }
+    }
+
+    /// The price of one ORE in SOL, if known.
+    pub fn price(&self) -> Option<f64> {
+        self.configured_price.or(self.fetched_price)
+    }
+
+    /// Fetches the latest ORE/SOL price, keeping the previous one if the request fails.
+    pub async fn refresh_price(&mut self) {
+        if self.configured_price.is_some() {
+            return;
+        }
+        match fetch_price(&self.price_url).await {
+            Ok(price) => self.fetched_price = Some(price),
+            Err(err) => warn!("Error fetching ORE price: {}", err),
+        }
+    }
+}
+
+async fn fetch_price(url: &str) -> Result<f64, Box<dyn std::error::Error>> {
+    // Expects a Jupiter price API response: {"data": {"<mint>": {"price": <sol>}}}
+    let body: serde_json::Value = reqwest::get(url).await?.json().await?;
+    body["data"][ore::MINT_ADDRESS.to_string()]["price"]
+        .as_f64()
+        .ok_or_else(|| "missing ORE price in response".into())
+}
+
+/// What the bundles of a mining round are expected to earn and cost.
+pub struct BundleEstimate {
+    /// ORE earned by the bundle, in base units.
+    pub reward: u64,
+    /// Value of the earned ORE, in lamports.
+    pub value: u64,
+    /// Signature and priority fees paid by the bundle, in lamports.
+    pub fees: u64,
+    /// Jito tips paid by the bundles, in lamports.
+    pub tip: u64,
+    /// Number of bundles the transactions are split across, each paying a tip.
+    pub bundles: u64,
+}
+
+impl BundleEstimate {
+    /// The estimate for `wallets` mining in `txs` transactions split across `bundles` bundles,
+    /// paying `priority_fees` lamports on top of their signature fees.
+    pub fn new(
+        wallets: usize,
+        txs: usize,
+        bundles: usize,
+        reward_rate: u64,
+        tip: u64,
+        priority_fees: u64,
+        ore_price: f64,
+    ) -> Self {
+        let reward = reward_rate.saturating_mul(wallets as u64);
+        let rewardf = (reward as f64) / 10f64.powf(ore::TOKEN_DECIMALS as f64);
+        let signatures = (wallets + txs) as u64;
+        let bundles = bundles as u64;
+        Self {
+            reward,
+            value: sol_to_lamports(rewardf * ore_price),
+            fees: signatures
+                .saturating_mul(LAMPORTS_PER_SIGNATURE)
+                .saturating_add(priority_fees),
+            tip: tip.saturating_mul(bundles),
+            bundles,
+        }
+    }
+
+    pub fn cost(&self) -> u64 {
+        self.fees.saturating_add(self.tip)
+    }
+
+    pub fn is_profitable(&self) -> bool {
+        self.value.ge(&self.cost())
+    }
+
+    /// The largest tip per bundle that keeps the bundles profitable.
+    pub fn max_tip(&self) -> u64 {
+        self.value.saturating_sub(self.fees) / self.bundles.max(1)
+    }
+
+    pub fn print(&self) {
+        info!(
+            "Expected: {} ORE ({} SOL) for {} SOL in fees and {} SOL in tips",
+            (self.reward as f64) / 10f64.powf(ore::TOKEN_DECIMALS as f64),
+            lamports_to_sol(self.value),
+            lamports_to_sol(self.fees),
+            lamports_to_sol(self.tip)
+        );
+    }
+}
+
+impl Miner {
+    /// Estimates the bundles sending `ixs` for `wallets` would take by packing them without
+    /// lookup tables, which can only take more transactions than a real send.
+    pub fn bundle_estimate(
+        &self,
+        ixs: &[Instruction],
+        wallets: usize,
+        reward_rate: u64,
+        tip: u64,
+        ore_price: f64,
+    ) -> BundleEstimate {
+        let feepayer = self.feepayer().pubkey();
+        let (txs, bundles, priority_fees) =
+            match self.pack_bundles(ixs, &feepayer, &[], Hash::default()) {
+                Ok(bundles) => {
+                    // Senders that pay no tip pay the priority fee on each transaction instead
+                    let priority_fees = bundles
+                        .iter()
+                        .flatten()
+                        .filter_map(|tx_ixs| compute_unit_limit(tx_ixs))
+                        .map(|compute_units| priority_fee(compute_units, self.priority_fee))
+                        .sum();
+                    let txs = bundles.iter().map(Vec::len).sum();
+                    (txs, bundles.len(), priority_fees)
+                }
+                // Fall back to the most it could take, a transaction per instruction at the
+                // largest compute unit limit
+                Err(_) => {
+                    let priority_fees = if self.sender.pays_tip() {
+                        0
+                    } else {
+                        priority_fee(CU_LIMIT_TRANSACTION as u64, self.priority_fee)
+                            .saturating_mul(ixs.len() as u64)
+                    };
+                    (ixs.len(), ixs.len().div_ceil(MAX_BUNDLE_TXS), priority_fees)
+                }
+            };
+        BundleEstimate::new(wallets, txs, bundles, reward_rate, tip, priority_fees, ore_price)
+    }
+}
@@ -1,24 +1,23 @@
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{commitment_config::CommitmentConfig, signature::Signer};
+use log::info;
+use solana_sdk::signature::Signer;
 use crate::{utils::proof_pubkey, Miner};
 use solana_sdk::signer::keypair::Keypair;
 impl Miner {
-    pub async fn register(&self) {
+    /// Registers every wallet without a proof, returning whether any needed registering.
+    pub async fn register(&self) -> bool {
         // let mut signers_to_use = Vec::new();
         let mut accounts_needed = Vec::new();
-        let client = RpcClient::new_with_commitment(self.cluster.clone(), CommitmentConfig::processed());
-
         let mut signers = Vec::new();
 
         for signer in self.signers() {
             let address = proof_pubkey(signer.pubkey());
             let pubkey = signer.pubkey();
         
-            if client.get_account(&address).await.is_err() {
+            if !matches!(self.chain.get_account(&address).await, Ok(Some(_))) {
                 accounts_needed.push(signer);
-                println!("{} is not registered, pubkey: {}", address, pubkey);
+                info!(wallet:% = pubkey, proof:% = address; "Not registered");
             } else {
-                println!("{} already registered, pubkey: {}", address, pubkey);
+                info!(wallet:% = pubkey, proof:% = address; "Already registered");
             }
         }
         
@@ -30,10 +29,10 @@ impl Miner {
         }).collect();
 
         if ixs.is_empty() {
-            println!("No new wallets to register, returning.");
-            return;
+            info!("No new wallets to register, returning.");
+            return false;
         } else {
-            println!("Registering {} new wallets with {} signers", accounts_needed.len(), signers.len());
+            info!("Registering {} new wallets with {} signers", accounts_needed.len(), signers.len());
         }
 
         // Sign and send transaction with the appropriate signers.
@@ -41,6 +40,7 @@ impl Miner {
             .await
             .expect("Transaction failed");
 
-        println!("Bundle sent wth uuid {}", uuid);
+        info!(bundle = uuid.join(","); "Registration bundle sent");
+        true
     }
 }
\ No newline at end of file
@@ -0,0 +1,286 @@
+use std::{collections::BTreeMap, fmt::Display, path::PathBuf, str::FromStr};
+
+use chrono::NaiveDate;
+use log::warn;
+use ore::{state::Proof, utils::AccountDeserialize};
+use serde::Serialize;
+use solana_program::{native_token::lamports_to_sol, pubkey::Pubkey};
+use solana_sdk::signature::Signer;
+
+use crate::{
+    history::{self, Ledger, Summary},
+    output::{self, OreAmount, OutputFormat, QueryOutput},
+    utils::{get_multiple_accounts_batched, ore_amount, proof_pubkey},
+    Miner,
+};
+
+/// Earnings and costs of one wallet, or of every wallet together.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct WalletReport {
+    pub wallet: String,
+    pub rounds: u64,
+    pub landed: u64,
+    pub failed: u64,
+    /// ORE earned over the report period, from the ledger.
+    pub ore_mined: OreAmount,
+    /// ORE the proof has earned since it was registered, if it is.
+    pub total_rewards: Option<OreAmount>,
+    /// ORE the proof holds unclaimed, if it is registered.
+    pub claimable_rewards: Option<OreAmount>,
+    pub tips_sol: f64,
+    pub fees_sol: f64,
+    /// Tips and fees together. An estimate: rounds count until the ledger shows all their
+    /// bundles failed, and ledgers written before fees were read from the sent transactions
+    /// hold estimated fees.
+    pub estimated_spent_sol: f64,
+    /// Estimated SOL spent per ORE mined, when any was.
+    pub estimated_cost_per_ore: Option<f64>,
+}
+
+impl WalletReport {
+    fn new(wallet: String, summary: &Summary, proof: Option<&Proof>) -> Self {
+        let spent = summary.tips.saturating_add(summary.fees);
+        let ore_mined = ore_amount(summary.reward);
+        Self {
+            wallet,
+            rounds: summary.rounds,
+            landed: summary.landed,
+            failed: summary.failed,
+            ore_mined: OreAmount::new(summary.reward),
+            total_rewards: proof.map(|proof| OreAmount::new(proof.total_rewards)),
+            claimable_rewards: proof.map(|proof| OreAmount::new(proof.claimable_rewards)),
+            tips_sol: lamports_to_sol(summary.tips),
+            fees_sol: lamports_to_sol(summary.fees),
+            estimated_spent_sol: lamports_to_sol(spent),
+            estimated_cost_per_ore: (ore_mined > 0.0).then(|| lamports_to_sol(spent) / ore_mined),
+        }
+    }
+}
+
+/// Profit and loss of every wallet over the report period.
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+    pub since: Option<NaiveDate>,
+    pub wallets: Vec<WalletReport>,
+    pub total: WalletReport,
+    pub fee_payer: String,
+    /// SOL the fee payer holds now, if it could be fetched.
+    pub fee_payer_balance_sol: Option<f64>,
+}
+
+impl Report {
+    /// Combines ledger summaries with the on-chain proofs of the same wallets.
+    pub fn new(
+        since: Option<NaiveDate>,
+        summaries: &BTreeMap<String, Summary>,
+        proofs: &BTreeMap<String, Proof>,
+        fee_payer: String,
+        fee_payer_balance: Option<u64>,
+    ) -> Self {
+        let mut total = Summary::default();
+        let wallets: Vec<_> = summaries
+            .iter()
+            .map(|(wallet, summary)| {
+                total.rounds += summary.rounds;
+                total.landed += summary.landed;
+                total.failed += summary.failed;
+                total.add_reward(summary.reward);
+                total.tips += summary.tips;
+                total.fees += summary.fees;
+                WalletReport::new(wallet.clone(), summary, proofs.get(wallet))
+            })
+            .collect();
+        let mut total = WalletReport::new("TOTAL".to_string(), &total, None);
+        if !proofs.is_empty() {
+            let sum = |rewards: fn(&Proof) -> u64| {
+                OreAmount::new(proofs.values().map(rewards).fold(0, u64::saturating_add))
+            };
+            total.total_rewards = Some(sum(|proof| proof.total_rewards));
+            total.claimable_rewards = Some(sum(|proof| proof.claimable_rewards));
+        }
+        Self {
+            since,
+            wallets,
+            total,
+            fee_payer,
+            fee_payer_balance_sol: fee_payer_balance.map(lamports_to_sol),
+        }
+    }
+}
+
+impl QueryOutput for Report {
+    fn text(&self) -> Vec<String> {
+        let mut lines = vec![];
+        if let Some(since) = self.since {
+            lines.push(format!("Since {}", since));
+        }
+        lines.push(format!(
+            "{:<44} {:>7} {:>7} {:>14} {:>14} {:>14} {:>13} {:>12}",
+            "WALLET",
+            "ROUNDS",
+            "LANDED",
+            "ORE MINED",
+            "TOTAL ORE",
+            "CLAIMABLE",
+            "EST SOL SPENT",
+            "EST SOL/ORE"
+        ));
+        for row in self.wallets.iter().chain([&self.total]) {
+            lines.push(format!(
+                "{:<44} {:>7} {:>7} {:>14} {:>14} {:>14} {:>13.6} {:>12}",
+                row.wallet,
+                row.rounds,
+                row.landed,
+                row.ore_mined,
+                optional(row.total_rewards.as_ref()),
+                optional(row.claimable_rewards.as_ref()),
+                row.estimated_spent_sol,
+                optional(row.estimated_cost_per_ore.map(sol))
+            ));
+        }
+        let balance = optional(self.fee_payer_balance_sol.map(sol));
+        lines.push(String::new());
+        lines.push(format!("Fee payer {} holds {} SOL", self.fee_payer, balance));
+        lines
+    }
+
+    fn table(&self) -> (Vec<&'static str>, Vec<Vec<String>>) {
+        let rows = self
+            .wallets
+            .iter()
+            .chain([&self.total])
+            .map(|row| {
+                vec![
+                    row.wallet.clone(),
+                    row.rounds.to_string(),
+                    row.landed.to_string(),
+                    row.failed.to_string(),
+                    row.ore_mined.to_string(),
+                    optional(row.total_rewards.as_ref()),
+                    optional(row.claimable_rewards.as_ref()),
+                    sol(row.tips_sol),
+                    sol(row.fees_sol),
+                    sol(row.estimated_spent_sol),
+                    optional(row.estimated_cost_per_ore.map(sol)),
+                ]
+            })
+            .collect();
+        let header = vec![
+            "WALLET",
+            "ROUNDS",
+            "LANDED",
+            "FAILED",
+            "ORE MINED",
+            "TOTAL ORE",
+            "CLAIMABLE ORE",
+            "TIPS SOL",
+            "FEES SOL",
+            "EST SOL SPENT",
+            "EST SOL/ORE",
+        ];
+        (header, rows)
+    }
+
+    /// The wallet rows and the total row with exact amounts, empty where unknown.
+    fn csv(&self) -> String {
+        let header = [
+            "wallet",
+            "rounds",
+            "landed",
+            "failed",
+            "ore_mined",
+            "total_rewards",
+            "claimable_rewards",
+            "tips_sol",
+            "fees_sol",
+            "estimated_spent_sol",
+            "estimated_cost_per_ore",
+        ];
+        let rows: Vec<Vec<String>> = self
+            .wallets
+            .iter()
+            .chain([&self.total])
+            .map(|row| {
+                vec![
+                    row.wallet.clone(),
+                    row.rounds.to_string(),
+                    row.landed.to_string(),
+                    row.failed.to_string(),
+                    row.ore_mined.to_string(),
+                    exact(row.total_rewards.as_ref()),
+                    exact(row.claimable_rewards.as_ref()),
+                    row.tips_sol.to_string(),
+                    row.fees_sol.to_string(),
+                    row.estimated_spent_sol.to_string(),
+                    exact(row.estimated_cost_per_ore),
+                ]
+            })
+            .collect();
+        output::render_csv(&header, &rows)
+    }
+}
+
+fn sol(value: f64) -> String {
+    format!("{:.6}", value)
+}
+
+fn optional(value: Option<impl Display>) -> String {
+    value.map_or("-".to_string(), |value| value.to_string())
+}
+
+fn exact(value: Option<impl Display>) -> String {
+    value.map_or(String::new(), |value| value.to_string())
+}
+
+impl Miner {
+    pub async fn report(&self, ledger: PathBuf, since: Option<NaiveDate>, format: OutputFormat) {
+        let events = match Ledger::new(ledger.clone()).read() {
+            Ok(events) => events,
+            Err(err) => {
+                output::fail(format!("Could not read ledger {}: {}", ledger.display(), err))
+            }
+        };
+        let events: Vec<_> = events
+            .into_iter()
+            .filter(|event| history::on_or_after(event, since))
+            .collect();
+        let (_, mut summaries) = history::summarize(&events);
+
+        // Report every configured wallet, even those without history in the period
+        for signer in self.signers() {
+            summaries.entry(signer.pubkey().to_string()).or_default();
+        }
+
+        // Fetch the proofs of every wallet in batched requests, reporting the ledger alone
+        // if they cannot be fetched
+        let wallets: Vec<_> = summaries
+            .keys()
+            .filter_map(|wallet| Pubkey::from_str(wallet).ok())
+            .collect();
+        let proof_addresses: Vec<_> = wallets.iter().map(|wallet| proof_pubkey(*wallet)).collect();
+        let mut proofs = BTreeMap::new();
+        match get_multiple_accounts_batched(self.chain.as_ref(), &proof_addresses).await {
+            Ok(accounts) => {
+                for (wallet, account) in wallets.iter().zip(accounts) {
+                    let proof = account
+                        .and_then(|account| Proof::try_from_bytes(&account.data).ok().copied());
+                    if let Some(proof) = proof {
+                        proofs.insert(wallet.to_string(), proof);
+                    }
+                }
+            }
+            Err(err) => warn!("Error fetching proofs, reporting the ledger only: {}", err),
+        }
+
+        let fee_payer = self.feepayer().pubkey();
+        let fee_payer_balance = self.chain.get_balance(&fee_payer).await.ok();
+        let report = Report::new(
+            since,
+            &summaries,
+            &proofs,
+            fee_payer.to_string(),
+            fee_payer_balance,
+        );
+        output::print(&report, format);
+    }
+}
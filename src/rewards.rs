@@ -1,24 +1,54 @@
 use std::str::FromStr;
 
+use ore::{state::Proof, utils::AccountDeserialize};
+use serde::Serialize;
 use solana_program::pubkey::Pubkey;
 use solana_sdk::signature::Signer;
 
-use crate::{utils::get_proof, Miner};
+use crate::{
+    output::{self, OreAmount, OutputFormat, QueryOutput},
+    utils::{get_account_data, proof_pubkey},
+    Miner,
+};
+
+/// The unclaimed mining rewards of a wallet.
+#[derive(Serialize)]
+pub struct RewardsOutput {
+    pub address: String,
+    /// ORE the wallet's proof holds unclaimed.
+    pub claimable_rewards: OreAmount,
+}
+
+impl QueryOutput for RewardsOutput {
+    fn text(&self) -> Vec<String> {
+        vec![format!("{} ORE", self.claimable_rewards)]
+    }
+
+    fn table(&self) -> (Vec<&'static str>, Vec<Vec<String>>) {
+        output::fields(&[
+            ("Address", self.address.clone()),
+            ("Claimable rewards", format!("{} ORE", self.claimable_rewards)),
+        ])
+    }
+}
 
 impl Miner {
-    pub async fn rewards(&self, address: Option<String>) {
+    pub async fn rewards(&self, address: Option<String>, format: OutputFormat) {
         let address = if let Some(address) = address {
-            if let Ok(address) = Pubkey::from_str(&address) {
-                address
-            } else {
-                println!("Invalid address: {:?}", address);
-                return;
-            }
+            Pubkey::from_str(&address)
+                .unwrap_or_else(|_| output::fail(format!("Invalid address `{}`", address)))
         } else {
             self.signers()[0].pubkey()
         };
-        let proof = get_proof(self.cluster.clone(), address).await;
-        let amount = (proof.claimable_rewards as f64) / 10f64.powf(ore::TOKEN_DECIMALS as f64);
-        println!("{:} ORE", amount);
+        let data = get_account_data(self.chain.as_ref(), &proof_pubkey(address))
+            .await
+            .unwrap_or_else(|err| output::fail(format!("Could not fetch the proof: {}", err)));
+        let proof = Proof::try_from_bytes(&data)
+            .unwrap_or_else(|_| output::fail("Could not parse the proof account"));
+        let rewards = RewardsOutput {
+            address: address.to_string(),
+            claimable_rewards: OreAmount::new(proof.claimable_rewards),
+        };
+        output::print(&rewards, format);
     }
 }
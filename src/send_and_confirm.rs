@@ -1,22 +1,26 @@
 use std::{
-    str,
+    fmt, str,
     sync::Arc,
 };
+use clap::ValueEnum;
+use log::{debug, warn};
 use thiserror::Error;
 use anyhow::Result;
-use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_program::instruction::Instruction;
 use solana_sdk::{
-    pubkey, commitment_config::CommitmentConfig, hash::Hash, message::{v0, VersionedMessage}, signature::{Keypair, Signer}, system_instruction::transfer, transaction::VersionedTransaction
+    compute_budget::ComputeBudgetInstruction,
+    address_lookup_table_account::AddressLookupTableAccount, pubkey, pubkey::Pubkey, hash::Hash, message::{v0, VersionedMessage}, packet::PACKET_DATA_SIZE, signature::{Keypair, Signer, SIGNATURE_BYTES}, system_instruction::{advance_nonce_account, transfer}, transaction::VersionedTransaction
 };
 use jito_protos::{
     auth::{auth_service_client::AuthServiceClient, Role},
-    bundle::Bundle,
+    bundle::{Bundle, BundleResult},
     convert::proto_packet_from_versioned_tx,
     searcher::{
         searcher_service_client::SearcherServiceClient, SendBundleRequest, SendBundleResponse,
+        SubscribeBundleResultsRequest,
     },
 };
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
 use tonic::{
     codegen::InterceptedService,
     transport,
@@ -24,6 +28,11 @@ use tonic::{
     Response, Status,
 };
 
+use crate::cu_limits::{estimate_compute_units, CU_LIMIT_TRANSACTION};
+use crate::nonce::{nonce_pubkey, DurableNonce};
+use crate::jito_http::JitoHttpClient;
+use crate::dry_run::{DryRunSender, TransactionCost};
+use crate::sender::{RpcSender, SenderKind, TransactionSender};
 use crate::token_authenticator::ClientInterceptor;
 use crate::Miner;
 
@@ -37,137 +46,571 @@ pub enum BlockEngineConnectionError {
 
 pub type BlockEngineConnectionResult<T> = Result<T, BlockEngineConnectionError>;
 
+/// How bundles reach the block engine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum BundleTransport {
+    /// The gRPC searcher API, authenticated with the auth keypair.
+    Grpc,
+    /// The JSON-RPC HTTP API, which needs no auth keypair.
+    Http,
+}
+
+impl BundleTransport {
+    /// Infers the transport from the block engine url, e.g. `.../api/v1/bundles` is HTTP.
+    pub fn from_url(url: &str) -> Self {
+        if url.contains("/api/v1/") {
+            BundleTransport::Http
+        } else {
+            BundleTransport::Grpc
+        }
+    }
+}
+
+/// A connection to the block engine over the configured transport.
+pub enum BundleSender {
+    Grpc(SearcherServiceClient<InterceptedService<Channel, ClientInterceptor>>),
+    Http(JitoHttpClient),
+}
+
+
+// Jito rejects bundles with more than 5 transactions
+pub const MAX_BUNDLE_TXS: usize = 5;
+
+pub const JITO_TIP_ACCOUNT: Pubkey = pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5");
+
+/// What one send put on chain.
+#[derive(Debug, Default)]
+pub struct SentBundles {
+    /// Ids the bundles or transactions can be tracked by.
+    pub ids: Vec<String>,
+    /// Tips and fees the sent transactions pay when they land.
+    pub cost: TransactionCost,
+    /// Every wallet that signed a sent transaction, the fee payer included.
+    pub signers: Vec<Pubkey>,
+}
+
+impl SentBundles {
+    fn add(&mut self, ids: Vec<String>, txs: &[VersionedTransaction]) {
+        self.ids.extend(ids);
+        for tx in txs {
+            self.cost.add(TransactionCost::new(tx));
+            let signers = tx.message.header().num_required_signatures as usize;
+            for pubkey in &tx.message.static_account_keys()[..signers] {
+                if !self.signers.contains(pubkey) {
+                    self.signers.push(*pubkey);
+                }
+            }
+        }
+    }
+}
 
-const CHUNK_SIZE: usize = 5;
+/// A send that failed, with whatever went out before it did.
+#[derive(Debug)]
+pub struct SendBundlesError {
+    pub sent: SentBundles,
+    pub err: Box<dyn std::error::Error>,
+}
+
+impl fmt::Display for SendBundlesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.sent.ids.is_empty() {
+            write!(f, "{}", self.err)
+        } else {
+            write!(f, "{} after sending {}", self.err, self.sent.ids.join(", "))
+        }
+    }
+}
+
+impl std::error::Error for SendBundlesError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.err.as_ref())
+    }
+}
+
+impl From<Box<dyn std::error::Error>> for SendBundlesError {
+    fn from(err: Box<dyn std::error::Error>) -> Self {
+        Self {
+            sent: SentBundles::default(),
+            err,
+        }
+    }
+}
 
 impl Miner {
     pub async fn send_and_confirm(
         &self,
         ixs: &[Instruction],
         signers: &[&Keypair],
-    ) -> Result<String, Box<dyn std::error::Error>> {
-        let feepayer = &self.feepayer();
-        let auth: Arc<Keypair> = Arc::new(self.auth());
-        let total_chunks = (ixs.len() + CHUNK_SIZE - 1) / CHUNK_SIZE;
-        //println!("Total chunks: {}", total_chunks);
-        let client = RpcClient::new_with_commitment(self.cluster.to_owned(), CommitmentConfig::processed());
-        let (hash, _slot) = client.get_latest_blockhash_with_commitment(CommitmentConfig::confirmed()).await?;
-        let mut versioned_txs = Vec::new();
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Ok(self.send_bundles(ixs, signers).await?.ids)
+    }
+
+    /// Packs, signs and sends instructions like `send_and_confirm`, also returning what the
+    /// transactions that went out cost. Sending stops at the first bundle that fails, and the
+    /// error keeps what was sent before it.
+    pub async fn send_bundles(
+        &self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<SentBundles, SendBundlesError> {
+        let bundles = self.prepare_bundles(ixs, signers).await?;
 
-        for (index, (ixs_chunk, signers_chunk)) in ixs.chunks(CHUNK_SIZE).zip(signers.chunks(CHUNK_SIZE)).enumerate() {
-            let mut vec_ixs = Vec::from(ixs_chunk);
-            let mut vec_signers = signers_chunk.to_vec();
+        // Connect to whatever delivers the transactions
+        let mut sender = self.get_sender().await?;
 
-            vec_signers.insert(0, feepayer); // Always include the feepayer to sign FIRSTTT
+        // Bundles sent as a slot starts give its leader the whole slot to include them
+        if self.sender.pays_tip() {
+            if let Some(slot) = self.wait_for_next_slot().await {
+                debug!(slot = slot; "Sending bundles as slot {} starts", slot);
+            }
+        }
 
+        let mut sent = SentBundles::default();
+        for versioned_txs in bundles {
+            if let Err(err) = self.send_bundle(sender.as_mut(), &versioned_txs, &mut sent).await {
+                // Transactions sent one by one may have landed before one failed
+                if !self.sender.pays_tip() && !self.dry_run {
+                    self.resolve_landed(&versioned_txs, &mut sent).await;
+                }
+                return Err(SendBundlesError { sent, err });
+            }
+        }
+        Ok(sent)
+    }
 
-            //println!("Ixs len: {}", vec_ixs.len());
-            //println!("Signing with accts:");
-            //for signer in &vec_signers {
-                //println!("{}", signer.pubkey()); // Print the public key of each signer
-            //}
+    /// Packs and signs instructions into the bundles `send_bundles` sends.
+    async fn prepare_bundles(
+        &self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Vec<Vec<VersionedTransaction>>, Box<dyn std::error::Error>> {
+        let hash = self.chain.get_latest_blockhash().await?;
+        let lookup_tables = self.get_lookup_tables().await?;
+        // Read the fee payer once, packing compiles a message per candidate transaction
+        let feepayer = self.feepayer();
+        let bundles = self.pack_bundles(ixs, &feepayer.pubkey(), &lookup_tables, hash)?;
+        let total_txs: usize = bundles.iter().map(Vec::len).sum();
+        let nonces = if self.durable_nonce {
+            Some(self.get_durable_nonces(total_txs).await?)
+        } else {
+            None
+        };
+        debug!(
+            "Packed {} instructions into {} transactions across {} bundles",
+            ixs.len(),
+            total_txs,
+            bundles.len()
+        );
+        self.sign_bundles(
+            &bundles,
+            &feepayer,
+            signers,
+            hash,
+            &lookup_tables,
+            nonces.as_deref(),
+        )
+    }
 
-            if index + 1 == total_chunks {
-                // This is the last chunk in the txs
-                let jito_tip_ix = transfer(
-                    &feepayer.pubkey(),
-                    &pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
-                    self.jito_fee,
-                );
-                vec_ixs.push(jito_tip_ix); // Push the jito tip instruction
+    /// Sends one signed bundle, adding it to `sent` once it goes out.
+    async fn send_bundle(
+        &self,
+        sender: &mut dyn TransactionSender,
+        versioned_txs: &[VersionedTransaction],
+        sent: &mut SentBundles,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let pays_tip = self.sender.pays_tip();
+
+        // Never pay a standalone tip for a bundle that would do nothing else
+        if pays_tip && self.separate_tip {
+            let (tip_tx, txs) = versioned_txs.split_last().unwrap();
+            if !self.any_tx_succeeds(txs).await? {
+                return Err(format!(
+                    "refusing to send bundle: only the tip transaction {} would succeed",
+                    tip_tx.signatures[0]
+                )
+                .into());
+            }
+        }
+
+        // Send the bundle of versioned transactions
+        let ids = sender
+            .send(versioned_txs)
+            .await
+            .map_err(|err| err as Box<dyn std::error::Error>)?;
+        if !self.dry_run {
+            self.metrics.bundles_sent(1);
+            // The rpc sender returns once every transaction has landed
+            if !pays_tip {
+                self.metrics.bundle_resolved(true, 0);
             }
+        }
+        sent.add(ids, versioned_txs);
+        Ok(())
+    }
 
-            // Create a versioned transaction for this chunk with respective signers and ixs
-            let versioned_tx = self.create_vtx(
-                hash,
-                &vec_ixs,
-                &vec_signers
-            ).await?;
+    /// Packs instructions into transactions and splits them into bundles, each tipped once,
+    /// returning the instructions of each transaction of each bundle. Senders that pay no tip
+    /// get compute budget instructions setting the priority fee in every transaction instead.
+    pub fn pack_bundles(
+        &self,
+        ixs: &[Instruction],
+        feepayer: &Pubkey,
+        lookup_tables: &[AddressLookupTableAccount],
+        hash: Hash,
+    ) -> Result<Vec<Vec<Vec<Instruction>>>, Box<dyn std::error::Error>> {
+        self.pack_bundles_with(ixs, feepayer, lookup_tables, hash, self.durable_nonce)
+    }
 
-            // Print tx size
-            //let serialized: Vec<u8> = bincode::serialize(&versioned_tx).unwrap();
-            //println!("Vtxn is {} bytes.", serialized.len());
+    /// Packs like `pack_bundles`, leaving room to advance a nonce in each transaction only if
+    /// `durable_nonce`, whether or not this run uses them.
+    pub fn pack_bundles_with(
+        &self,
+        ixs: &[Instruction],
+        feepayer: &Pubkey,
+        lookup_tables: &[AddressLookupTableAccount],
+        hash: Hash,
+        durable_nonce: bool,
+    ) -> Result<Vec<Vec<Vec<Instruction>>>, Box<dyn std::error::Error>> {
+        let jito_tip_ix = transfer(feepayer, &JITO_TIP_ACCOUNT, self.jito_tip());
+
+        // Leave room to advance a nonce account in each transaction when using durable nonces
+        let mut reserved_ixs: Vec<Instruction> = durable_nonce
+            .then(|| advance_nonce_account(&nonce_pubkey(feepayer, 0), feepayer))
+            .into_iter()
+            .collect();
 
-            versioned_txs.push(versioned_tx);
+        if !self.sender.pays_tip() {
+            // Reserve the largest compute budget instructions, then size the limit to each chunk
+            reserved_ixs.extend(self.compute_budget_ixs(CU_LIMIT_TRANSACTION));
+            let chunks = self.pack_instructions(ixs, &reserved_ixs, feepayer, lookup_tables, hash)?;
+            return Ok(chunks
+                .chunks(MAX_BUNDLE_TXS)
+                .map(|bundle| {
+                    bundle
+                        .iter()
+                        .map(|chunk| {
+                            let compute_units = reserved_ixs
+                                .iter()
+                                .chain(chunk)
+                                .map(estimate_compute_units)
+                                .sum();
+                            let mut tx_ixs = self.compute_budget_ixs(compute_units).to_vec();
+                            tx_ixs.extend_from_slice(chunk);
+                            tx_ixs
+                        })
+                        .collect()
+                })
+                .collect());
         }
 
-        // Get searcher client
-        let mut searcher_client = self.get_searcher_client(&self.be_url, &auth)
-            .await?;
+        // Pack as many instructions into each transaction as fit, then split into bundles,
+        // saving the last slot of each bundle for the tip when it is sent on its own
+        if !self.separate_tip {
+            reserved_ixs.push(jito_tip_ix.clone());
+        }
+        let chunks = self.pack_instructions(ixs, &reserved_ixs, feepayer, lookup_tables, hash)?;
+        let chunks_per_bundle = if self.separate_tip {
+            MAX_BUNDLE_TXS - 1
+        } else {
+            MAX_BUNDLE_TXS
+        };
+        Ok(chunks
+            .chunks(chunks_per_bundle)
+            .map(|bundle| {
+                let mut bundle_ixs = bundle.to_vec();
+                if self.separate_tip {
+                    bundle_ixs.push(vec![jito_tip_ix.clone()]); // The tip is its own transaction
+                } else if let Some(last) = bundle_ixs.last_mut() {
+                    last.push(jito_tip_ix.clone()); // Push the jito tip instruction
+                }
+                bundle_ixs
+            })
+            .collect())
+    }
 
-        // Send the bundle of versioned transactions
-        let send_response = self.send_bundle_no_wait(&versioned_txs, &mut searcher_client)
-            .await?;
-        
-        Ok(send_response.into_inner().uuid)
+    /// Adds the transactions of a failed bundle that landed anyway to `sent`, by their
+    /// signature status, so a retry only resends the rest.
+    async fn resolve_landed(&self, versioned_txs: &[VersionedTransaction], sent: &mut SentBundles) {
+        for tx in versioned_txs {
+            let signature = tx.signatures[0];
+            match self.chain.get_signature_status(&signature).await {
+                Ok(Some(Ok(()))) => sent.add(vec![signature.to_string()], std::slice::from_ref(tx)),
+                Ok(_) => {}
+                Err(err) => warn!("Error resolving transaction {}: {}", signature, err),
+            }
+        }
     }
 
-    async fn create_vtx(
+    /// Compute budget instructions limiting a transaction to `compute_units` at the priority fee.
+    fn compute_budget_ixs(&self, compute_units: u32) -> [Instruction; 2] {
+        [
+            ComputeBudgetInstruction::set_compute_unit_limit(compute_units),
+            ComputeBudgetInstruction::set_compute_unit_price(self.priority_fee),
+        ]
+    }
+
+    /// Compiles and signs packed bundles, advancing one nonce per transaction when given nonces.
+    pub fn sign_bundles(
         &self,
+        bundles: &[Vec<Vec<Instruction>>],
+        feepayer: &Keypair,
+        signers: &[&Keypair],
         hash: Hash,
+        lookup_tables: &[AddressLookupTableAccount],
+        nonces: Option<&[DurableNonce]>,
+    ) -> Result<Vec<Vec<VersionedTransaction>>, Box<dyn std::error::Error>> {
+        let mut tx_index = 0;
+        let mut signed_bundles = Vec::with_capacity(bundles.len());
+        for bundle_ixs in bundles {
+            let mut versioned_txs = Vec::with_capacity(bundle_ixs.len());
+            for ixs in bundle_ixs {
+                let mut vec_ixs = ixs.clone();
+                let mut tx_hash = hash;
+
+                // Each transaction advances its own nonce first and signs with its value
+                if let Some(nonces) = nonces {
+                    let nonce = &nonces[tx_index];
+                    vec_ixs.insert(0, nonce.advance_ix(&feepayer.pubkey()));
+                    tx_hash = nonce.blockhash;
+                }
+                tx_index += 1;
+                let vec_signers = required_signers(&vec_ixs, feepayer, signers);
+
+                // Create a versioned transaction for this chunk with respective signers and ixs
+                let versioned_tx = create_vtx(
+                    &feepayer.pubkey(),
+                    tx_hash,
+                    &vec_ixs,
+                    lookup_tables,
+                    &vec_signers
+                )?;
+                versioned_txs.push(versioned_tx);
+            }
+            signed_bundles.push(versioned_txs);
+        }
+        Ok(signed_bundles)
+    }
+
+    /// Greedily groups instructions into transactions that stay within the chunk size,
+    /// the compute unit limit and the packet size, with room left for the reserved instructions.
+    fn pack_instructions(
+        &self,
+        ixs: &[Instruction],
+        reserved_ixs: &[Instruction],
+        feepayer: &Pubkey,
+        lookup_tables: &[AddressLookupTableAccount],
+        hash: Hash,
+    ) -> Result<Vec<Vec<Instruction>>, Box<dyn std::error::Error>> {
+        let mut chunks = Vec::new();
+        let mut chunk: Vec<Instruction> = Vec::new();
+        for ix in ixs {
+            chunk.push(ix.clone());
+            if self.fits(&chunk, reserved_ixs, feepayer, lookup_tables, hash)? {
+                continue;
+            }
+            let ix = chunk.pop().unwrap();
+            if !chunk.is_empty() {
+                chunks.push(std::mem::take(&mut chunk));
+                chunk.push(ix);
+            }
+            if !self.fits(&chunk, reserved_ixs, feepayer, lookup_tables, hash)? || chunk.is_empty()
+            {
+                return Err("instruction is too large to fit in a transaction".into());
+            }
+        }
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+        Ok(chunks)
+    }
+
+    fn fits(
+        &self,
         ixs: &[Instruction],
-        payers: &[&Keypair],
-    ) -> Result<VersionedTransaction, Box<dyn std::error::Error>> {
-        let tx = VersionedTransaction::try_new(
-            VersionedMessage::V0(v0::Message::try_compile(
-                &self.feepayer().pubkey(),
-                ixs,
-                &[],
-                hash,
-            )?),
-            payers,
-        )?;
-    
-        Ok(tx)
-    }
-
-    async fn get_searcher_client(
+        reserved_ixs: &[Instruction],
+        feepayer: &Pubkey,
+        lookup_tables: &[AddressLookupTableAccount],
+        hash: Hash,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        if self.chunk_size.is_some_and(|chunk_size| ixs.len().gt(&chunk_size)) {
+            return Ok(false);
+        }
+        let mut vec_ixs = reserved_ixs.to_vec();
+        vec_ixs.extend_from_slice(ixs);
+        let compute_units: u32 = vec_ixs.iter().map(estimate_compute_units).sum();
+        if compute_units.gt(&CU_LIMIT_TRANSACTION) {
+            return Ok(false);
+        }
+        let message = VersionedMessage::V0(v0::Message::try_compile(
+            feepayer,
+            &vec_ixs,
+            lookup_tables,
+            hash,
+        )?);
+        Ok(transaction_size(&message).le(&PACKET_DATA_SIZE))
+    }
+
+    /// Simulates the transactions one by one, returning whether any of them succeeds.
+    /// Each is simulated against the current state rather than after the ones before it, so a
+    /// transaction that depends on an earlier one in the bundle counts as failing. The first
+    /// transaction sees the state it would on chain, so a bundle whose first transaction
+    /// succeeds is never refused.
+    async fn any_tx_succeeds(
+        &self,
+        txs: &[VersionedTransaction],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        for tx in txs {
+            if self.chain.simulate_transaction(tx).await?.is_none() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    pub async fn get_bundle_sender(&self) -> Result<BundleSender, Box<dyn std::error::Error>> {
+        match self.bundle_transport {
+            BundleTransport::Grpc => {
+                let auth: Arc<Keypair> = Arc::new(self.auth());
+                let searcher_client = self.get_searcher_client(&self.be_url, &auth).await?;
+                Ok(BundleSender::Grpc(searcher_client))
+            }
+            BundleTransport::Http => Ok(BundleSender::Http(JitoHttpClient::new(self.be_url.clone()))),
+        }
+    }
+
+    /// The sender selected for this run, connected and ready to send.
+    pub async fn get_sender(&self) -> Result<Box<dyn TransactionSender>, Box<dyn std::error::Error>> {
+        if self.dry_run {
+            return Ok(Box::new(DryRunSender::new(self.chain.clone(), self.simulate)));
+        }
+        Ok(match self.sender {
+            SenderKind::Jito => Box::new(self.get_bundle_sender().await?),
+            SenderKind::Rpc => Box::new(RpcSender::new(self.chain.clone())),
+        })
+    }
+
+    pub async fn get_searcher_client(
         &self,
         block_engine_url: &str,
         auth_keypair: &Arc<Keypair>,
     ) -> BlockEngineConnectionResult<
         SearcherServiceClient<InterceptedService<Channel, ClientInterceptor>>,
     > {
-        let auth_channel = self.create_grpc_channel(block_engine_url).await?;
-        let client_interceptor = ClientInterceptor::new(
-            AuthServiceClient::new(auth_channel),
-            auth_keypair,
-            Role::Searcher,
-        )
-        .await?;
-    
-        let searcher_channel = self.create_grpc_channel(block_engine_url).await?;
-        let searcher_client =
-            SearcherServiceClient::with_interceptor(searcher_channel, client_interceptor);
-        Ok(searcher_client)
+        connect_searcher(block_engine_url, auth_keypair).await
     }
 
-    async fn create_grpc_channel(&self, url: &str) -> BlockEngineConnectionResult<Channel> {
-        let mut endpoint = Endpoint::from_shared(url.to_string()).expect("invalid url");
-        if url.starts_with("https") {
-            endpoint = endpoint.tls_config(tonic::transport::ClientTlsConfig::new())?;
-        }
-        Ok(endpoint.connect().await?)
+    /// Streams the results of every bundle sent with the auth keypair into a channel.
+    pub async fn subscribe_bundle_results(
+        &self,
+    ) -> BlockEngineConnectionResult<UnboundedReceiver<BundleResult>> {
+        let auth: Arc<Keypair> = Arc::new(self.auth());
+        let mut searcher_client = self.get_searcher_client(&self.be_url, &auth).await?;
+        let mut stream = searcher_client
+            .subscribe_bundle_results(SubscribeBundleResultsRequest {})
+            .await?
+            .into_inner();
+        let (sender, receiver) = unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok(Some(result)) = stream.message().await {
+                if sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(receiver)
     }
 
-    pub async fn send_bundle_no_wait(
-        &self,
-        transactions: &[VersionedTransaction],
-        searcher_client: &mut SearcherServiceClient<InterceptedService<Channel, ClientInterceptor>>,
-    ) -> Result<Response<SendBundleResponse>, Status> {
-        // convert them to packets + send over
-        let packets: Vec<_> = transactions
-            .iter()
-            .map(proto_packet_from_versioned_tx)
-            .collect();
-    
-        searcher_client
-            .send_bundle(SendBundleRequest {
-                bundle: Some(Bundle {
-                    header: None,
-                    packets,
-                }),
-            })
-            .await
+    pub async fn create_grpc_channel(&self, url: &str) -> BlockEngineConnectionResult<Channel> {
+        connect_grpc_channel(url).await
     }
-}
\ No newline at end of file
+}
+
+/// An authenticated searcher client, for tasks that outlive the borrow of the miner.
+pub async fn connect_searcher(
+    block_engine_url: &str,
+    auth_keypair: &Arc<Keypair>,
+) -> BlockEngineConnectionResult<SearcherServiceClient<InterceptedService<Channel, ClientInterceptor>>>
+{
+    let auth_channel = connect_grpc_channel(block_engine_url).await?;
+    let client_interceptor = ClientInterceptor::new(
+        AuthServiceClient::new(auth_channel),
+        auth_keypair,
+        Role::Searcher,
+    )
+    .await?;
+
+    let searcher_channel = connect_grpc_channel(block_engine_url).await?;
+    let searcher_client =
+        SearcherServiceClient::with_interceptor(searcher_channel, client_interceptor);
+    Ok(searcher_client)
+}
+
+async fn connect_grpc_channel(url: &str) -> BlockEngineConnectionResult<Channel> {
+    let mut endpoint = Endpoint::from_shared(url.to_string()).expect("invalid url");
+    if url.starts_with("https") {
+        endpoint = endpoint.tls_config(tonic::transport::ClientTlsConfig::new())?;
+    }
+    Ok(endpoint.connect().await?)
+}
+
+pub async fn send_bundle_no_wait(
+    transactions: &[VersionedTransaction],
+    searcher_client: &mut SearcherServiceClient<InterceptedService<Channel, ClientInterceptor>>,
+) -> Result<Response<SendBundleResponse>, Status> {
+    // convert them to packets + send over
+    let packets: Vec<_> = transactions
+        .iter()
+        .map(proto_packet_from_versioned_tx)
+        .collect();
+
+    searcher_client
+        .send_bundle(SendBundleRequest {
+            bundle: Some(Bundle {
+                header: None,
+                packets,
+            }),
+        })
+        .await
+}
+
+/// The feepayer followed by every other keypair the instructions require, once.
+fn required_signers<'a>(
+    ixs: &[Instruction],
+    feepayer: &'a Keypair,
+    signers: &[&'a Keypair],
+) -> Vec<&'a Keypair> {
+    let mut vec_signers = vec![feepayer]; // Always include the feepayer to sign FIRSTTT
+    for signer in signers {
+        let pubkey = signer.pubkey();
+        let is_required = ixs.iter().any(|ix| {
+            ix.accounts
+                .iter()
+                .any(|meta| meta.is_signer && meta.pubkey.eq(&pubkey))
+        });
+        if is_required && !vec_signers.iter().any(|s| s.pubkey().eq(&pubkey)) {
+            vec_signers.push(*signer);
+        }
+    }
+    vec_signers
+}
+
+/// Size of the signed transaction for a message, without signing it.
+fn transaction_size(message: &VersionedMessage) -> usize {
+    let signatures = message.header().num_required_signatures as usize;
+    // The signature count is a compact-u16, which is one byte for fewer than 128
+    1 + signatures * SIGNATURE_BYTES + message.serialize().len()
+}
+
+fn create_vtx(
+    feepayer: &Pubkey,
+    hash: Hash,
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    payers: &[&Keypair],
+) -> Result<VersionedTransaction, Box<dyn std::error::Error>> {
+    let tx = VersionedTransaction::try_new(
+        VersionedMessage::V0(v0::Message::try_compile(feepayer, ixs, lookup_tables, hash)?),
+        payers,
+    )?;
+    Ok(tx)
+}
@@ -0,0 +1,108 @@
+use std::{
+    error::Error,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use clap::ValueEnum;
+use solana_sdk::{
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+};
+use tokio::time::sleep;
+
+use crate::{
+    chain::Chain,
+    send_and_confirm::{send_bundle_no_wait, BundleSender},
+};
+
+// Seconds the rpc sender waits for a transaction to land before giving up
+const CONFIRM_TIMEOUT: u64 = 30;
+
+// Milliseconds between signature status polls
+const CONFIRM_POLL_INTERVAL: u64 = 500;
+
+pub type SendResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+/// A sent transaction that landed with an error.
+#[derive(Debug, thiserror::Error)]
+#[error("transaction {signature} failed: {err}")]
+pub struct FailedTransaction {
+    pub signature: Signature,
+    #[source]
+    pub err: TransactionError,
+}
+
+/// How signed transactions reach the chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SenderKind {
+    /// Tipped bundles sent to the block engine over the bundle transport.
+    Jito,
+    /// Transactions sent one by one to the RPC node, paying the priority fee instead of a tip.
+    Rpc,
+}
+
+impl SenderKind {
+    /// Whether transactions sent this way pay the Jito tip, rather than a priority fee.
+    pub fn pays_tip(&self) -> bool {
+        self.eq(&SenderKind::Jito)
+    }
+}
+
+/// Delivers the signed transactions of a bundle, so commands need not know how they reach the chain.
+#[async_trait]
+pub trait TransactionSender: Send {
+    /// Sends the transactions of one bundle in order, returning the ids they can be tracked by.
+    async fn send(&mut self, txs: &[VersionedTransaction]) -> SendResult<Vec<String>>;
+}
+
+#[async_trait]
+impl TransactionSender for BundleSender {
+    async fn send(&mut self, txs: &[VersionedTransaction]) -> SendResult<Vec<String>> {
+        let uuid = match self {
+            BundleSender::Grpc(searcher_client) => {
+                send_bundle_no_wait(txs, searcher_client)
+                    .await?
+                    .into_inner()
+                    .uuid
+            }
+            BundleSender::Http(http_client) => http_client.send_bundle(txs).await?,
+        };
+        Ok(vec![uuid])
+    }
+}
+
+/// Sends each transaction to the chain and waits for it to land before sending the next.
+pub struct RpcSender {
+    chain: Arc<dyn Chain>,
+}
+
+impl RpcSender {
+    pub fn new(chain: Arc<dyn Chain>) -> Self {
+        Self { chain }
+    }
+}
+
+#[async_trait]
+impl TransactionSender for RpcSender {
+    async fn send(&mut self, txs: &[VersionedTransaction]) -> SendResult<Vec<String>> {
+        let mut signatures = Vec::with_capacity(txs.len());
+        for tx in txs {
+            let signature = self.chain.send_transaction(tx).await?;
+            let started = Instant::now();
+            loop {
+                match self.chain.get_signature_status(&signature).await? {
+                    Some(Ok(())) => break,
+                    Some(Err(err)) => return Err(FailedTransaction { signature, err }.into()),
+                    None if started.elapsed().gt(&Duration::from_secs(CONFIRM_TIMEOUT)) => {
+                        return Err(format!("transaction {} did not land", signature).into())
+                    }
+                    None => sleep(Duration::from_millis(CONFIRM_POLL_INTERVAL)).await,
+                }
+            }
+            signatures.push(signature.to_string());
+        }
+        Ok(signatures)
+    }
+}
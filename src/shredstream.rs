@@ -0,0 +1,177 @@
+use std::{sync::Arc, time::Duration};
+
+use jito_protos::{
+    auth::{auth_service_client::AuthServiceClient, Role},
+    shared::Socket,
+    shredstream::{shredstream_client::ShredstreamClient, Heartbeat},
+};
+use log::{debug, error, info, warn};
+use solana_sdk::signature::Keypair;
+use tokio::{
+    net::UdpSocket,
+    time::{sleep, timeout},
+};
+use tonic::{codegen::InterceptedService, transport::Channel};
+
+use crate::{
+    send_and_confirm::BlockEngineConnectionResult, token_authenticator::ClientInterceptor, Miner,
+};
+
+// Seconds to wait before retrying a failed heartbeat
+const HEARTBEAT_RETRY_DELAY: u64 = 5;
+
+// Fewest milliseconds between heartbeats, however short a ttl the proxy returns
+const MIN_HEARTBEAT_INTERVAL: u64 = 100;
+
+// Milliseconds to wait for the next slot before sending anyway, about a slot and a half
+const SLOT_WAIT_TIMEOUT: u64 = 600;
+
+// Byte range of the slot in a shred's common header. Every shred starts with a 64 byte
+// signature and a 1 byte variant, followed by the slot as a little endian u64.
+const SHRED_SIGNATURE_SIZE: usize = 64;
+const SHRED_VARIANT_SIZE: usize = 1;
+const SHRED_SLOT_OFFSET: usize = SHRED_SIGNATURE_SIZE + SHRED_VARIANT_SIZE;
+const SHRED_SLOT_END: usize = SHRED_SLOT_OFFSET + 8;
+
+// Largest shred payload the proxy forwards
+const SHRED_PAYLOAD_SIZE: usize = 1228;
+
+// Milliseconds to wait after a failed receive, doubling on each failure in a row up to the max
+const RECV_RETRY_DELAY: u64 = 10;
+const MAX_RECV_RETRY_DELAY: u64 = 1000;
+
+impl Miner {
+    pub async fn shredstream(&self, ip: String, port: u16, regions: Vec<String>) {
+        // Report each new slot as soon as its first shred arrives
+        let mut slots = self.latest_slot.subscribe();
+        let report = async {
+            while slots.changed().await.is_ok() {
+                let slot = *slots.borrow_and_update();
+                debug!(slot = slot; "Slot {} started", slot);
+            }
+        };
+        tokio::select! {
+            _ = self.receive_shreds(ip, port, regions) => {}
+            _ = report => {}
+        }
+    }
+
+    /// Subscribes to shreds from the block engine and publishes the newest slot they belong to
+    /// in `latest_slot`, for the send path to time bundles by. Returns only if it cannot start.
+    pub async fn receive_shreds(&self, ip: String, port: u16, regions: Vec<String>) {
+        let auth: Arc<Keypair> = Arc::new(self.auth());
+        let mut client = match self.get_shredstream_client(&self.be_url, &auth).await {
+            Ok(client) => client,
+            Err(err) => {
+                error!("Error connecting to block engine: {}", err);
+                return;
+            }
+        };
+        let socket = match UdpSocket::bind(("0.0.0.0", port)).await {
+            Ok(socket) => socket,
+            Err(err) => {
+                error!("Error binding shred socket on port {}: {}", port, err);
+                return;
+            }
+        };
+
+        // Shreds only keep flowing while heartbeats arrive within their ttl
+        let heartbeat = Heartbeat {
+            socket: Some(Socket {
+                ip: ip.clone(),
+                port: port as i64,
+            }),
+            regions: regions.clone(),
+        };
+        tokio::spawn(async move {
+            loop {
+                match client.send_heartbeat(heartbeat.clone()).await {
+                    Ok(response) => {
+                        let ttl_ms = response.into_inner().ttl_ms as u64;
+                        sleep(heartbeat_interval(ttl_ms)).await;
+                    }
+                    Err(err) => {
+                        warn!("Error sending heartbeat: {}", err);
+                        sleep(Duration::from_secs(HEARTBEAT_RETRY_DELAY)).await;
+                    }
+                }
+            }
+        });
+        info!("Receiving shreds on {}:{} from {}", ip, port, regions.join(", "));
+
+        let mut buf = [0u8; SHRED_PAYLOAD_SIZE];
+        let mut retry_delay = RECV_RETRY_DELAY;
+        loop {
+            let len = match socket.recv(&mut buf).await {
+                Ok(len) => {
+                    retry_delay = RECV_RETRY_DELAY;
+                    len
+                }
+                Err(err) => {
+                    // Back off so a socket that keeps failing does not spin
+                    warn!("Error receiving shred, retrying in {}ms: {}", retry_delay, err);
+                    sleep(Duration::from_millis(retry_delay)).await;
+                    retry_delay = retry_delay.saturating_mul(2).min(MAX_RECV_RETRY_DELAY);
+                    continue;
+                }
+            };
+            let Some(slot) = shred_slot(&buf[..len]) else {
+                continue;
+            };
+            self.latest_slot.send_if_modified(|latest| {
+                let newer = slot.gt(latest);
+                if newer {
+                    *latest = slot;
+                }
+                newer
+            });
+        }
+    }
+
+    /// Waits for the first shred of the next slot, so a bundle reaches the leader with the
+    /// whole slot ahead of it. Returns at once without a shredstream, and gives up after a slot
+    /// if shreds stop arriving.
+    pub async fn wait_for_next_slot(&self) -> Option<u64> {
+        let mut slots = self.latest_slot.subscribe();
+        if slots.borrow_and_update().eq(&0) {
+            return None;
+        }
+        match timeout(Duration::from_millis(SLOT_WAIT_TIMEOUT), slots.changed()).await {
+            Ok(Ok(())) => Some(*slots.borrow()),
+            _ => None,
+        }
+    }
+
+    async fn get_shredstream_client(
+        &self,
+        block_engine_url: &str,
+        auth_keypair: &Arc<Keypair>,
+    ) -> BlockEngineConnectionResult<ShredstreamClient<InterceptedService<Channel, ClientInterceptor>>>
+    {
+        let auth_channel = self.create_grpc_channel(block_engine_url).await?;
+        let client_interceptor = ClientInterceptor::new(
+            AuthServiceClient::new(auth_channel),
+            auth_keypair,
+            Role::ShredstreamSubscriber,
+        )
+        .await?;
+
+        let shredstream_channel = self.create_grpc_channel(block_engine_url).await?;
+        Ok(ShredstreamClient::with_interceptor(
+            shredstream_channel,
+            client_interceptor,
+        ))
+    }
+}
+
+/// The slot a raw shred belongs to, if it is long enough to have one.
+fn shred_slot(shred: &[u8]) -> Option<u64> {
+    let bytes = shred.get(SHRED_SLOT_OFFSET..SHRED_SLOT_END)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// How long to wait between heartbeats: half their ttl, so one can be lost without shreds
+/// stopping, but never so often that a zero ttl spins.
+fn heartbeat_interval(ttl_ms: u64) -> Duration {
+    Duration::from_millis((ttl_ms / 2).max(MIN_HEARTBEAT_INTERVAL))
+}
@@ -0,0 +1,176 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use chrono::DateTime;
+use ore::{state::Proof, utils::AccountDeserialize};
+use serde::Serialize;
+use solana_client::client_error::{ClientErrorKind, Result as ClientResult};
+use solana_program::{native_token::lamports_to_sol, program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::signature::Signer;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Account as TokenAccount;
+
+use crate::{
+    history::{Ledger, LedgerEvent},
+    output::{self, OreAmount, OutputFormat, QueryOutput},
+    utils::{get_multiple_accounts_batched, proof_pubkey},
+    Miner,
+};
+
+/// Balances, rewards and activity of one miner wallet.
+#[derive(Serialize)]
+pub struct WalletStatus {
+    pub wallet: String,
+    /// Whether the wallet has a proof account to mine with.
+    pub registered: bool,
+    pub sol_balance: f64,
+    /// ORE in the wallet's token account, `None` if it does not exist.
+    pub ore_balance: Option<OreAmount>,
+    pub claimable_rewards: Option<OreAmount>,
+    pub total_rewards: Option<OreAmount>,
+    pub total_hashes: Option<u64>,
+    /// When the last round of the wallet was sent, from the ledger.
+    pub last_hash_at: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct StatusOutput {
+    pub wallets: Vec<WalletStatus>,
+}
+
+impl QueryOutput for StatusOutput {
+    fn text(&self) -> Vec<String> {
+        self.wallets
+            .iter()
+            .map(|status| {
+                let ore_balance = status
+                    .ore_balance
+                    .as_ref()
+                    .map_or("no ORE token account".to_string(), |balance| {
+                        format!("{} ORE", balance)
+                    });
+                let mut line =
+                    format!("{}: {} SOL, {}", status.wallet, status.sol_balance, ore_balance);
+                match (&status.claimable_rewards, &status.total_rewards) {
+                    (Some(claimable), Some(total)) => line.push_str(&format!(
+                        ", {} ORE claimable of {} ORE mined",
+                        claimable, total
+                    )),
+                    _ => line.push_str(", not registered"),
+                }
+                if let Some(last_hash_at) = status.last_hash_at.as_ref() {
+                    line.push_str(&format!(", last hash at {}", last_hash_at));
+                }
+                line
+            })
+            .collect()
+    }
+
+    fn table(&self) -> (Vec<&'static str>, Vec<Vec<String>>) {
+        let optional = |value: &Option<OreAmount>| {
+            value.as_ref().map_or("-".to_string(), |value| value.to_string())
+        };
+        let rows = self
+            .wallets
+            .iter()
+            .map(|status| {
+                vec![
+                    status.wallet.clone(),
+                    if status.registered { "yes" } else { "no" }.to_string(),
+                    status.sol_balance.to_string(),
+                    optional(&status.ore_balance),
+                    optional(&status.claimable_rewards),
+                    optional(&status.total_rewards),
+                    status.last_hash_at.clone().unwrap_or("-".to_string()),
+                ]
+            })
+            .collect();
+        let header = vec![
+            "WALLET",
+            "REGISTERED",
+            "SOL",
+            "ORE",
+            "CLAIMABLE ORE",
+            "TOTAL ORE",
+            "LAST HASH",
+        ];
+        (header, rows)
+    }
+}
+
+impl Miner {
+    pub async fn status(&self, ledger: PathBuf, format: OutputFormat) {
+        let wallets: Vec<Pubkey> = self.signers().iter().map(|signer| signer.pubkey()).collect();
+        if wallets.is_empty() {
+            output::fail("No miner wallets in keys.txt");
+        }
+        match self.wallet_status(&wallets, &Ledger::new(ledger)).await {
+            Ok(status) => output::print(&status, format),
+            Err(err) => output::fail(format!("Could not fetch the miner accounts: {}", err)),
+        }
+    }
+
+    /// The status of each wallet, fetching its account, token account and proof together.
+    pub async fn wallet_status(
+        &self,
+        wallets: &[Pubkey],
+        ledger: &Ledger,
+    ) -> ClientResult<StatusOutput> {
+        let addresses: Vec<Pubkey> = wallets
+            .iter()
+            .flat_map(|wallet| {
+                [
+                    *wallet,
+                    get_associated_token_address(wallet, &ore::MINT_ADDRESS),
+                    proof_pubkey(*wallet),
+                ]
+            })
+            .collect();
+        let accounts = get_multiple_accounts_batched(self.chain.as_ref(), &addresses).await?;
+        if accounts.len() != addresses.len() {
+            return Err(ClientErrorKind::Custom(format!(
+                "Expected {} accounts, the RPC node returned {}",
+                addresses.len(),
+                accounts.len()
+            ))
+            .into());
+        }
+
+        // A ledger that cannot be read only leaves the last hash times unknown
+        let mut last_rounds: HashMap<String, i64> = HashMap::new();
+        for event in ledger.read().unwrap_or_default() {
+            if let LedgerEvent::Round { timestamp, wallet, .. } = event {
+                let last = last_rounds.entry(wallet).or_default();
+                *last = (*last).max(timestamp);
+            }
+        }
+
+        let wallets = wallets
+            .iter()
+            .zip(accounts.chunks(3))
+            .map(|(wallet, accounts)| {
+                let proof = accounts[2]
+                    .as_ref()
+                    .and_then(|account| Proof::try_from_bytes(&account.data).ok());
+                let token_account = accounts[1]
+                    .as_ref()
+                    .and_then(|account| TokenAccount::unpack(&account.data).ok());
+                let last_hash_at = last_rounds
+                    .get(&wallet.to_string())
+                    .and_then(|timestamp| DateTime::from_timestamp(*timestamp, 0))
+                    .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string());
+                WalletStatus {
+                    wallet: wallet.to_string(),
+                    registered: proof.is_some(),
+                    sol_balance: lamports_to_sol(accounts[0].as_ref().map_or(0, |a| a.lamports)),
+                    ore_balance: token_account
+                        .map(|token_account| OreAmount::new(token_account.amount)),
+                    claimable_rewards: proof.map(|proof| OreAmount::new(proof.claimable_rewards)),
+                    total_rewards: proof.map(|proof| OreAmount::new(proof.total_rewards)),
+                    total_hashes: proof.map(|proof| proof.total_hashes),
+                    last_hash_at,
+                }
+            })
+            .collect();
+        Ok(StatusOutput { wallets })
+    }
+}
@@ -0,0 +1,131 @@
+use std::str::FromStr;
+
+use log::{error, info};
+use solana_program::{native_token::lamports_to_sol, program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::{
+    native_token::sol_to_lamports,
+    signature::{Keypair, Signer},
+    system_instruction::transfer,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+
+use crate::{
+    utils::{get_multiple_accounts_batched, ore_ui_amount},
+    Miner,
+};
+
+impl Miner {
+    pub async fn sweep(&self, to: String, keep: f64) {
+        let Ok(to) = Pubkey::from_str(&to) else {
+            error!("Invalid address: {:?}", to);
+            return;
+        };
+        let signers = self.signers();
+        let signers: Vec<&Keypair> = signers.iter().collect();
+        match self
+            .sweep_wallets(&signers, to, sol_to_lamports(keep))
+            .await
+        {
+            Ok((0, 0)) => {}
+            Ok((total_ore, total_sol)) => info!(
+                "Swept {} ORE and {} SOL to {}",
+                ore_ui_amount(total_ore),
+                lamports_to_sol(total_sol),
+                to
+            ),
+            Err(err) => error!("Error sweeping wallets: {}", err),
+        }
+    }
+
+    /// Moves the ORE of every wallet to `to`'s token account and its SOL to `to`, leaving
+    /// `keep` lamports, and never less than the rent exempt minimum, in each wallet.
+    /// Returns the ORE and lamports swept.
+    pub async fn sweep_wallets(
+        &self,
+        signers: &[&Keypair],
+        to: Pubkey,
+        keep: u64,
+    ) -> Result<(u64, u64), Box<dyn std::error::Error>> {
+        // Wallets stay rent exempt, so sweeping never closes one
+        let rent = self
+            .chain
+            .get_minimum_balance_for_rent_exemption(0)
+            .await
+            .map_err(|err| format!("could not fetch the rent exempt minimum: {}", err))?;
+        let keep = keep.max(rent);
+
+        // Fetch every miner wallet and ORE token account in batched requests
+        let pubkeys: Vec<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+        let token_account_pubkeys: Vec<Pubkey> = pubkeys
+            .iter()
+            .map(|pubkey| get_associated_token_address(pubkey, &ore::MINT_ADDRESS))
+            .collect();
+        let wallets = get_multiple_accounts_batched(self.chain.as_ref(), &pubkeys)
+            .await
+            .map_err(|err| format!("could not fetch the miner accounts: {}", err))?;
+        let token_accounts =
+            get_multiple_accounts_batched(self.chain.as_ref(), &token_account_pubkeys)
+                .await
+                .map_err(|err| format!("could not fetch the miner token accounts: {}", err))?;
+
+        // Move ORE to the treasury wallet's token account and SOL to the treasury wallet
+        let to_token_account = get_associated_token_address(&to, &ore::MINT_ADDRESS);
+        let mut ixs = Vec::new();
+        let mut ix_signers: Vec<&Keypair> = Vec::new();
+        let (mut total_ore, mut total_sol) = (0u64, 0u64);
+        for (i, signer) in signers.iter().enumerate() {
+            let pubkey = signer.pubkey();
+            let ore_amount = token_accounts[i]
+                .as_ref()
+                .and_then(|account| spl_token::state::Account::unpack(&account.data).ok())
+                .map_or(0, |token_account| token_account.amount);
+            let sol_amount = wallets[i]
+                .as_ref()
+                .map_or(0, |account| account.lamports.saturating_sub(keep));
+            if ore_amount.gt(&0) {
+                ixs.push(spl_token::instruction::transfer(
+                    &spl_token::id(),
+                    &token_account_pubkeys[i],
+                    &to_token_account,
+                    &pubkey,
+                    &[],
+                    ore_amount,
+                )?);
+                total_ore += ore_amount;
+            }
+            if sol_amount.gt(&0) {
+                ixs.push(transfer(&pubkey, &to, sol_amount));
+                total_sol += sol_amount;
+            }
+            if ore_amount.gt(&0) || sol_amount.gt(&0) {
+                let sol_amountf = lamports_to_sol(sol_amount);
+                info!(wallet:% = pubkey; "{} ORE, {} SOL", ore_ui_amount(ore_amount), sol_amountf);
+                ix_signers.push(signer);
+            }
+        }
+        if ix_signers.is_empty() {
+            info!("Nothing to sweep, returning.");
+            return Ok((0, 0));
+        }
+
+        // The receiving token account only needs to exist when ORE is moved to it
+        if total_ore.gt(&0) {
+            ixs.insert(
+                0,
+                create_associated_token_account_idempotent(
+                    &self.feepayer().pubkey(),
+                    &to,
+                    &ore::MINT_ADDRESS,
+                    &spl_token::id(),
+                ),
+            );
+        }
+
+        // Sign and send transaction with the appropriate signers.
+        let uuids = self.send_and_confirm(&ixs, &ix_signers).await?;
+        info!(bundle = uuids.join(","); "Sweep bundles sent");
+        Ok((total_ore, total_sol))
+    }
+}
@@ -0,0 +1,240 @@
+use std::time::Duration;
+
+use jito_protos::{
+    bundle::{bundle_result::Result as BundleOutcome, Dropped, Processed},
+    searcher::{searcher_service_client::SearcherServiceClient, GetTipAccountsRequest},
+};
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction::transfer,
+    transaction::{TransactionError, VersionedTransaction},
+};
+use tokio::time::{sleep, timeout};
+use tonic::Code;
+
+use super::{fake_miner, test_miner};
+use crate::{
+    mock_block_engine::MockBlockEngine,
+    send_and_confirm::{BundleSender, JITO_TIP_ACCOUNT},
+    sender::TransactionSender,
+    tip::TipController,
+};
+
+// Access tokens this long-lived are never refreshed during a test
+const ACCESS_TOKEN_TTL: Duration = Duration::from_secs(60 * 60);
+
+// Access tokens this short-lived are refreshed as soon as the client connects
+const EXPIRING_ACCESS_TOKEN_TTL: Duration = Duration::from_secs(60);
+
+// Longest a test waits on the mock block engine
+const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `count` small transfers out of `wallet`, enough to fill several transactions.
+fn transfers(wallet: &Keypair, count: usize) -> Vec<Instruction> {
+    (0..count)
+        .map(|_| transfer(&wallet.pubkey(), &Keypair::new().pubkey(), 1))
+        .collect()
+}
+
+fn pays_tip(tx: &VersionedTransaction) -> bool {
+    tx.message.static_account_keys().contains(&JITO_TIP_ACCOUNT)
+}
+
+#[tokio::test]
+async fn authenticates_searcher_requests() {
+    let (_engine, url) = MockBlockEngine::start(ACCESS_TOKEN_TTL).await;
+    let miner = test_miner(url.clone(), &Keypair::new());
+
+    let BundleSender::Grpc(mut searcher_client) = miner.get_bundle_sender().await.unwrap() else {
+        panic!("expected a grpc bundle sender");
+    };
+    let accounts = searcher_client
+        .get_tip_accounts(GetTipAccountsRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .accounts;
+    assert_eq!(accounts, vec![JITO_TIP_ACCOUNT.to_string()]);
+
+    // Requests without the interceptor carry no access token
+    let err = SearcherServiceClient::connect(url)
+        .await
+        .unwrap()
+        .get_tip_accounts(GetTipAccountsRequest {})
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn refreshes_expiring_access_token() {
+    let (engine, url) = MockBlockEngine::start(EXPIRING_ACCESS_TOKEN_TTL).await;
+    let feepayer = Keypair::new();
+    let (miner, _) = fake_miner(url, &feepayer);
+    let wallet = Keypair::new();
+    let hash = Hash::new_unique();
+    let bundles = miner
+        .pack_bundles(&transfers(&wallet, 1), &feepayer.pubkey(), &[], hash)
+        .unwrap();
+    let txs = miner
+        .sign_bundles(&bundles, &feepayer, &[&wallet], hash, &[], None)
+        .unwrap()
+        .remove(0);
+
+    // The refresh happens in a connection's background, so keep one sender and send
+    // until a bundle carries the new token
+    let mut bundle_sender = miner.get_bundle_sender().await.unwrap();
+    timeout(TEST_TIMEOUT, async {
+        loop {
+            bundle_sender.send(&txs).await.unwrap();
+            let bundle = engine.bundles().pop().unwrap();
+            if engine.latest_access_token().eq(&Some(bundle.access_token)) && engine.refreshes() > 0 {
+                break;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("access token was not refreshed");
+    assert_eq!(engine.refreshes(), 1);
+}
+
+#[tokio::test]
+async fn sends_packed_bundles_tipped_in_their_last_transaction() {
+    let (engine, url) = MockBlockEngine::start(ACCESS_TOKEN_TTL).await;
+    let feepayer = Keypair::new();
+    let (miner, _) = fake_miner(url, &feepayer);
+    let wallet = Keypair::new();
+
+    let uuids = miner
+        .send_and_confirm(&transfers(&wallet, 12), &[&wallet])
+        .await
+        .unwrap();
+
+    // Five transfers fit each transaction at the test's chunk size, all in one bundle
+    let bundles = engine.bundles();
+    assert_eq!(bundles.len(), 1);
+    assert_eq!(uuids, vec![bundles[0].uuid.clone()]);
+    let txs = &bundles[0].transactions;
+    assert_eq!(txs.len(), 3);
+    assert!(txs.iter().all(|tx| {
+        tx.message.static_account_keys()[0].eq(&feepayer.pubkey())
+            && tx.verify_with_results().iter().all(|valid| *valid)
+    }));
+    let tipped: Vec<_> = txs.iter().map(pays_tip).collect();
+    assert_eq!(tipped, vec![false, false, true]);
+}
+
+#[tokio::test]
+async fn sends_the_tip_as_a_transaction_of_its_own() {
+    let (engine, url) = MockBlockEngine::start(ACCESS_TOKEN_TTL).await;
+    let feepayer = Keypair::new();
+    let (mut miner, _) = fake_miner(url, &feepayer);
+    miner.separate_tip = true;
+    let wallet = Keypair::new();
+
+    miner
+        .send_and_confirm(&transfers(&wallet, 12), &[&wallet])
+        .await
+        .unwrap();
+
+    let bundles = engine.bundles();
+    assert_eq!(bundles.len(), 1);
+    let (tip_tx, txs) = bundles[0].transactions.split_last().unwrap();
+    assert_eq!(txs.len(), 3);
+    assert!(!txs.iter().any(pays_tip));
+    assert!(pays_tip(tip_tx));
+    assert_eq!(tip_tx.message.instructions().len(), 1);
+    assert_eq!(tip_tx.signatures.len(), 1);
+}
+
+#[tokio::test]
+async fn correlates_bundle_results_with_sent_bundles() {
+    let (engine, url) = MockBlockEngine::start(ACCESS_TOKEN_TTL).await;
+    let (miner, _) = fake_miner(url, &Keypair::new());
+    let wallet = Keypair::new();
+    engine.script(vec![
+        BundleOutcome::Processed(Processed {
+            validator_identity: Keypair::new().pubkey().to_string(),
+            slot: 1,
+            bundle_index: 0,
+        }),
+        BundleOutcome::Dropped(Dropped { reason: 0 }),
+    ]);
+    let mut bundle_results = miner.subscribe_bundle_results().await.unwrap();
+    let mut tip_controller = TipController::new(1000, 100, 10_000);
+
+    let mut uuids = vec![];
+    for _ in 0..2 {
+        let uuid = miner
+            .send_and_confirm(&transfers(&wallet, 1), &[&wallet])
+            .await
+            .unwrap()
+            .pop()
+            .unwrap();
+        tip_controller.sent(uuid.clone(), miner.jito_tip());
+        uuids.push(uuid);
+    }
+    for uuid in uuids.iter() {
+        let result = timeout(TEST_TIMEOUT, bundle_results.recv())
+            .await
+            .expect("no bundle result streamed")
+            .unwrap();
+        assert_eq!(&result.bundle_id, uuid);
+        tip_controller.observe(&result);
+    }
+
+    // The landed bundle lowered the tip and a single drop is not enough to raise it
+    assert!(tip_controller.pending().is_empty());
+    assert_eq!(tip_controller.tip(), 900);
+}
+
+#[tokio::test]
+async fn refuses_to_pay_a_standalone_tip_for_a_failing_bundle() {
+    let (engine, url) = MockBlockEngine::start(Duration::from_secs(60 * 60)).await;
+    let feepayer = Keypair::new();
+    let (mut miner, chain) = fake_miner(url, &feepayer);
+    miner.separate_tip = true;
+    chain.fail_transactions(TransactionError::AccountNotFound);
+
+    let ix = transfer(&feepayer.pubkey(), &Pubkey::new_unique(), 1);
+    let err = miner.send_and_confirm(&[ix], &[]).await.unwrap_err();
+    assert!(err.to_string().contains("only the tip transaction"));
+    assert!(engine.bundles().is_empty());
+    assert!(chain.sent().is_empty());
+}
+
+#[tokio::test]
+async fn simulates_each_transaction_of_a_tipped_bundle_alone() {
+    let (engine, url) = MockBlockEngine::start(ACCESS_TOKEN_TTL).await;
+    let feepayer = Keypair::new();
+    let (mut miner, chain) = fake_miner(url, &feepayer);
+    miner.separate_tip = true;
+    miner.chunk_size = Some(1);
+    let wallet = Keypair::new();
+    let recipient = Pubkey::new_unique();
+
+    // The second transfer only succeeds once the first has funded the wallet, so it fails
+    // when simulated alone, but the first succeeds and the bundle goes out
+    let err = TransactionError::InsufficientFundsForRent { account_index: 1 };
+    chain.fail_transactions_using(recipient, err);
+    let ixs = [
+        transfer(&feepayer.pubkey(), &wallet.pubkey(), 1_000_000),
+        transfer(&wallet.pubkey(), &recipient, 1_000_000),
+    ];
+    miner.send_and_confirm(&ixs, &[&wallet]).await.unwrap();
+    let bundles = engine.bundles();
+    assert_eq!(bundles.len(), 1);
+    assert_eq!(bundles[0].transactions.len(), 3);
+
+    // Without the transfer it depends on, nothing in the bundle succeeds and it is refused
+    let err = miner
+        .send_and_confirm(&[ixs[1].clone()], &[&wallet])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("only the tip transaction"));
+    assert!(engine.bundles().is_empty());
+}
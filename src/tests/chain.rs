@@ -0,0 +1,54 @@
+use ore::{
+    state::{Proof, Treasury},
+    TREASURY_ADDRESS,
+};
+use solana_program::{keccak::Hash as KeccakHash, pubkey::Pubkey};
+use solana_sdk::signature::Keypair;
+
+use super::{fake_miner, ore_token_account, program_account};
+use crate::utils::{get_proof, get_token_account, get_treasury, ore_ui_amount, proof_pubkey};
+
+#[tokio::test]
+async fn reads_treasury_and_proofs_from_chain() {
+    let (miner, chain) = fake_miner(String::new(), &Keypair::new());
+    let authority = Pubkey::new_unique();
+    let treasury = Treasury {
+        bump: 255,
+        admin: Pubkey::new_unique(),
+        difficulty: KeccakHash::new_unique().into(),
+        last_reset_at: 100,
+        reward_rate: 1_000,
+        total_claimed_rewards: 0,
+    };
+    let proof = Proof {
+        authority,
+        claimable_rewards: 5_000,
+        hash: KeccakHash::new_unique().into(),
+        total_hashes: 3,
+        total_rewards: 7_000,
+    };
+    chain.set_account(TREASURY_ADDRESS, program_account::<Treasury>(treasury.to_bytes()));
+    chain.set_account(proof_pubkey(authority), program_account::<Proof>(proof.to_bytes()));
+
+    let fetched = get_treasury(miner.chain.as_ref()).await;
+    assert_eq!(fetched.reward_rate, treasury.reward_rate);
+    assert_eq!(fetched.difficulty, treasury.difficulty);
+    let fetched = get_proof(miner.chain.as_ref(), authority).await;
+    assert_eq!(fetched.claimable_rewards, proof.claimable_rewards);
+    assert_eq!(fetched.total_hashes, proof.total_hashes);
+}
+
+#[tokio::test]
+async fn formats_token_balances_in_ore() {
+    let (miner, chain) = fake_miner(String::new(), &Keypair::new());
+    let address = Pubkey::new_unique();
+    chain.set_account(address, ore_token_account(Pubkey::new_unique(), 1_500_000_000));
+
+    let token_account = get_token_account(miner.chain.as_ref(), &address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(ore_ui_amount(token_account.amount), "1.5");
+    let missing = get_token_account(miner.chain.as_ref(), &Pubkey::new_unique()).await;
+    assert!(missing.unwrap().is_none());
+}
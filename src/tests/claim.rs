@@ -0,0 +1,78 @@
+use ore::state::Proof;
+use solana_program::{keccak::Hash as KeccakHash, pubkey::Pubkey};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+};
+
+use super::{fake_miner, program_account};
+use crate::{fake_chain::FakeChain, sender::SenderKind, utils::proof_pubkey};
+
+const ORE: u64 = 10u64.pow(ore::TOKEN_DECIMALS as u32);
+
+fn proof(wallet: &Keypair, claimable_rewards: u64) -> Proof {
+    Proof {
+        authority: wallet.pubkey(),
+        claimable_rewards,
+        hash: KeccakHash::new_unique().into(),
+        total_hashes: 0,
+        total_rewards: claimable_rewards,
+    }
+}
+
+fn set_proof(chain: &FakeChain, wallet: &Keypair, claimable_rewards: u64) {
+    let proof = proof(wallet, claimable_rewards);
+    chain.set_account(proof_pubkey(wallet.pubkey()), program_account::<Proof>(proof.to_bytes()));
+}
+
+#[tokio::test]
+async fn claims_only_registered_wallets_with_rewards() {
+    let (mut miner, chain) = fake_miner(String::new(), &Keypair::new());
+    miner.sender = SenderKind::Rpc;
+    let wallets: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    set_proof(&chain, &wallets[0], 5 * ORE);
+    set_proof(&chain, &wallets[1], 0);
+    let claimers: Vec<&Keypair> = wallets.iter().collect();
+    let beneficiary = Pubkey::new_unique();
+
+    let claims = miner.claim_rewards(&claimers, beneficiary, None).await.unwrap();
+    assert_eq!(claims, vec![(wallets[0].pubkey(), 5 * ORE)]);
+    assert_eq!(chain.sent().len(), 1);
+
+    // A requested amount caps each claim at the wallet's rewards
+    let claims = miner.claim_rewards(&claimers, beneficiary, Some(ORE)).await.unwrap();
+    assert_eq!(claims, vec![(wallets[0].pubkey(), ORE)]);
+
+    // A proof that cannot be read is reported rather than claimed around
+    chain.set_account(proof_pubkey(wallets[2].pubkey()), Account::new(1, 3, &ore::ID));
+    let err = miner.claim_rewards(&claimers, beneficiary, None).await.unwrap_err();
+    assert!(err.to_string().contains("could not parse the proof"));
+    assert_eq!(chain.sent().len(), 2);
+}
+
+#[tokio::test]
+async fn auto_claims_wallets_at_or_over_the_threshold() {
+    let (mut miner, chain) = fake_miner(String::new(), &Keypair::new());
+    miner.sender = SenderKind::Rpc;
+    let wallets: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let signers: Vec<&Keypair> = wallets.iter().collect();
+    let beneficiary = Pubkey::new_unique();
+
+    let proofs = [
+        proof(&wallets[0], 3 * ORE),
+        proof(&wallets[1], 2 * ORE),
+        proof(&wallets[2], ORE),
+    ];
+    miner.auto_claim(&signers, &proofs, 2 * ORE, beneficiary).await;
+    let sent = chain.sent();
+    assert_eq!(sent.len(), 1);
+    let signed = &sent[0].message.static_account_keys()[..sent[0].signatures.len()];
+    assert!(signed.contains(&wallets[0].pubkey()));
+    assert!(signed.contains(&wallets[1].pubkey()));
+    assert!(!signed.contains(&wallets[2].pubkey()));
+
+    // Nothing is sent for wallets without rewards, even at a zero threshold
+    let proofs: Vec<_> = wallets.iter().map(|wallet| proof(wallet, 0)).collect();
+    miner.auto_claim(&signers, &proofs, 0, beneficiary).await;
+    assert_eq!(chain.sent().len(), 1);
+}
@@ -0,0 +1,34 @@
+use ore::{state::Bus, BUS_ADDRESSES, BUS_COUNT};
+use solana_sdk::signature::Keypair;
+
+use super::{fake_miner, program_account};
+use crate::contention::BusContention;
+
+#[tokio::test]
+async fn finds_the_least_contended_bus_with_enough_rewards() {
+    let (miner, chain) = fake_miner(String::new(), &Keypair::new());
+    let reward_rate = 1_000;
+    for (id, address) in BUS_ADDRESSES.iter().enumerate() {
+        // Bus 2 cannot pay out four rounds, so it is never picked however quiet it is
+        let rewards = if id.eq(&2) { 4 * reward_rate } else { 1_000_000 };
+        let bus = Bus {
+            id: id as u64,
+            rewards,
+        };
+        chain.set_account(*address, program_account::<Bus>(bus.to_bytes()));
+    }
+    let contention = BusContention::new();
+    for id in (0..BUS_COUNT).filter(|id| !matches!(id, 2 | 5)) {
+        contention.record(id);
+    }
+
+    for _ in 0..10 {
+        let bus = miner.find_uncontended_bus(reward_rate, &contention).await.unwrap();
+        assert_eq!(bus.id, 5);
+    }
+
+    // Without counts every bus with enough rewards is as good as another
+    contention.clear();
+    let bus = miner.find_uncontended_bus(reward_rate, &contention).await.unwrap();
+    assert_ne!(bus.id, 2);
+}
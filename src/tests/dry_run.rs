@@ -0,0 +1,35 @@
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    system_instruction::transfer,
+};
+
+use super::fake_miner;
+use crate::{chain::Chain, send_and_confirm::JITO_TIP_ACCOUNT, sender::SenderKind};
+
+#[tokio::test]
+async fn dry_run_signs_without_sending() {
+    let feepayer = Keypair::new();
+    let (mut miner, chain) = fake_miner(String::new(), &feepayer);
+    miner.dry_run = true;
+    miner.simulate = true;
+    let ixs: Vec<_> = (0..7)
+        .map(|_| transfer(&feepayer.pubkey(), &Pubkey::new_unique(), 1))
+        .collect();
+
+    // Each sender's transactions were built and signed as it would send them, and none
+    // reached the chain or counted as sent
+    for sender in [SenderKind::Jito, SenderKind::Rpc] {
+        miner.sender = sender;
+        let hash = chain.get_latest_blockhash().await.unwrap();
+        let packed = miner.pack_bundles(&ixs, &feepayer.pubkey(), &[], hash).unwrap();
+        let signatures = miner.send_and_confirm(&ixs, &[]).await.unwrap();
+        assert_eq!(signatures.len(), packed.iter().map(Vec::len).sum::<usize>());
+        let tipped = packed.concat().concat().iter().any(|ix| {
+            ix.accounts.iter().any(|account| account.pubkey.eq(&JITO_TIP_ACCOUNT))
+        });
+        assert_eq!(tipped, sender.pays_tip());
+    }
+    assert!(chain.sent().is_empty());
+    assert!(miner.metrics.render().contains("ore_bundles_sent_total 0\n"));
+}
@@ -0,0 +1,40 @@
+use solana_program::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, system_program};
+use solana_sdk::{account::Account, signature::Keypair};
+
+use super::{fake_miner, sent_programs};
+use crate::sender::SenderKind;
+
+#[tokio::test]
+async fn funds_wallets_up_to_the_target() {
+    let (mut miner, chain) = fake_miner(String::new(), &Keypair::new());
+    miner.sender = SenderKind::Rpc;
+    let wallets: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+    chain.set_account(
+        wallets[0],
+        Account::new(LAMPORTS_PER_SOL / 5, 0, &system_program::ID),
+    );
+    chain.set_account(
+        wallets[2],
+        Account::new(LAMPORTS_PER_SOL, 0, &system_program::ID),
+    );
+
+    // The empty wallet gets the whole target and the one above it nothing
+    let funded = miner
+        .fund_wallets(&wallets, LAMPORTS_PER_SOL / 2)
+        .await
+        .unwrap();
+    assert_eq!(funded, LAMPORTS_PER_SOL * 3 / 10 + LAMPORTS_PER_SOL / 2);
+    let transfers = sent_programs(&chain)
+        .into_iter()
+        .filter(|program| program.eq(&system_program::ID))
+        .count();
+    assert_eq!(transfers, 2);
+
+    // Nothing is sent once every wallet holds the target
+    let funded = miner
+        .fund_wallets(&wallets[2..], LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    assert_eq!(funded, 0);
+    assert_eq!(chain.sent().len(), 1);
+}
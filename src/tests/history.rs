@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use ore::state::Proof;
+use solana_program::{keccak::Hash as KeccakHash, native_token::LAMPORTS_PER_SOL};
+
+use crate::{
+    history::{summarize, Ledger, LedgerEvent, Summary},
+    output::QueryOutput,
+    report::Report,
+};
+
+// 2024-04-01 12:00:00 UTC
+const DAY_ONE: i64 = 1_711_972_800;
+const DAY: i64 = 86_400;
+
+pub(super) fn round(timestamp: i64, wallet: &str, bundle: &str) -> LedgerEvent {
+    LedgerEvent::Round {
+        timestamp,
+        wallet: wallet.to_string(),
+        proof_hash: String::new(),
+        total_rewards: None,
+        hash: String::new(),
+        nonce: 0,
+        difficulty: String::new(),
+        bus: 0,
+        bundles: vec![bundle.to_string()],
+        tip: 1000,
+        fee: 5000,
+    }
+}
+
+fn outcome(timestamp: i64, bundle: &str, landed: bool) -> LedgerEvent {
+    LedgerEvent::Outcome {
+        timestamp,
+        bundle: bundle.to_string(),
+        landed,
+        slot: None,
+    }
+}
+
+#[test]
+fn summarizes_the_ledger_by_day_and_wallet() {
+    let path = std::env::temp_dir().join(format!("ore-test-ledger-{}.jsonl", std::process::id()));
+    std::fs::remove_file(&path).ok();
+    let ledger = Ledger::new(path.clone());
+    for event in [
+        round(DAY_ONE, "a", "b1"),
+        outcome(DAY_ONE + 10, "b1", true),
+        LedgerEvent::Reward { timestamp: DAY_ONE + 60, wallet: "a".to_string(), delta: 700 },
+        round(DAY_ONE + DAY, "a", "b2"),
+        round(DAY_ONE + DAY, "b", "b2"),
+        outcome(DAY_ONE + DAY + 30, "b2", false),
+        round(DAY_ONE + DAY + 60, "b", "b3"),
+    ] {
+        ledger.append(event);
+    }
+    let events = ledger.read().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(events.len(), 7);
+
+    let (days, wallets) = summarize(&events);
+    let first = days[&NaiveDate::from_ymd_opt(2024, 4, 1).unwrap()];
+    assert_eq!((first.rounds, first.landed, first.failed, first.reward), (1, 1, 0, 700));
+    assert_eq!((first.tips, first.fees), (1000, 5000));
+
+    // Failed rounds cost nothing, rounds without an outcome yet still count their costs
+    let second = days[&NaiveDate::from_ymd_opt(2024, 4, 2).unwrap()];
+    assert_eq!((second.rounds, second.landed, second.failed), (3, 0, 2));
+    assert_eq!((second.tips, second.fees), (1000, 5000));
+    assert_eq!(wallets["a"].rounds, 2);
+    assert_eq!(wallets["b"].failed, 1);
+}
+
+#[test]
+fn reports_cost_per_ore_with_on_chain_rewards() {
+    let ore = 10u64.pow(ore::TOKEN_DECIMALS as u32);
+    let mined = Summary {
+        rounds: 4,
+        landed: 3,
+        failed: 1,
+        reward: 2 * ore,
+        tips: LAMPORTS_PER_SOL / 10,
+        fees: LAMPORTS_PER_SOL / 10,
+    };
+    let summaries = BTreeMap::from([("a".to_string(), mined), ("b".to_string(), Summary::default())]);
+    let proof = Proof {
+        authority: Default::default(),
+        claimable_rewards: ore,
+        hash: KeccakHash::default().into(),
+        total_hashes: 0,
+        total_rewards: 5 * ore,
+    };
+    let proofs = BTreeMap::from([("a".to_string(), proof)]);
+    let report = Report::new(None, &summaries, &proofs, "payer".to_string(), Some(LAMPORTS_PER_SOL));
+
+    assert_eq!(report.wallets[0].estimated_cost_per_ore, Some(0.1));
+    assert_eq!(report.wallets[0].total_rewards.as_ref().map(|r| r.amount), Some(5 * ore));
+    // Unregistered wallets and wallets that mined nothing have no rewards or cost per ORE
+    assert_eq!(report.wallets[1].claimable_rewards, None);
+    assert_eq!(report.wallets[1].estimated_cost_per_ore, None);
+    assert_eq!(report.total.estimated_spent_sol, 0.2);
+    assert_eq!(report.total.claimable_rewards.as_ref().map(|r| r.amount), Some(ore));
+
+    let csv = report.csv();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("wallet,rounds,landed,failed,ore_mined,"));
+    assert_eq!(lines[2], "b,0,0,0,0,,,0,0,0,");
+    assert!(lines[3].starts_with("TOTAL,4,3,1,2,5,1,"));
+}
@@ -0,0 +1,110 @@
+use serde_json::json;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction::transfer,
+    transaction::VersionedTransaction,
+};
+
+use super::fake_miner;
+use crate::{
+    bundle::BundleStatus,
+    jito_http::MAX_BUNDLE_STATUS_IDS,
+    mock_block_engine::MockBundleApi,
+    send_and_confirm::{BundleTransport, JITO_TIP_ACCOUNT},
+};
+
+#[tokio::test]
+async fn sends_bundles_over_json_rpc() {
+    let (api, url) = MockBundleApi::start(vec![("sendBundle", json!("bundle-uuid"))]).await;
+    let feepayer = Keypair::new();
+    let (mut miner, _) = fake_miner(url, &feepayer);
+    miner.bundle_transport = BundleTransport::Http;
+    let ixs: Vec<_> = (0..7)
+        .map(|_| transfer(&feepayer.pubkey(), &Pubkey::new_unique(), 1))
+        .collect();
+
+    let uuids = miner.send_and_confirm(&ixs, &[]).await.unwrap();
+    assert_eq!(uuids, vec!["bundle-uuid".to_string()]);
+
+    // Transactions go out base-58 encoded, in order, with the tip in the last one
+    let requests = api.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["method"], "sendBundle");
+    let txs: Vec<VersionedTransaction> = requests[0]["params"][0]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tx| {
+            let bytes = bs58::decode(tx.as_str().unwrap()).into_vec().unwrap();
+            bincode::deserialize(&bytes).unwrap()
+        })
+        .collect();
+    assert_eq!(txs.len(), 2);
+    assert!(txs[1].message.static_account_keys().contains(&JITO_TIP_ACCOUNT));
+}
+
+#[tokio::test]
+async fn reads_bundle_statuses_in_chunks_the_api_accepts() {
+    let (api, url) = MockBundleApi::start(vec![
+        (
+            "getInflightBundleStatuses",
+            json!({
+                "context": { "slot": 100 },
+                "value": [
+                    { "bundle_id": "landed", "status": "Landed", "landed_slot": 90 },
+                    { "bundle_id": "failed", "status": "Failed", "landed_slot": null },
+                    null,
+                ],
+            }),
+        ),
+        (
+            "getBundleStatuses",
+            json!({
+                "context": { "slot": 100 },
+                "value": [{
+                    "bundle_id": "landed",
+                    "transactions": ["signature"],
+                    "slot": 91,
+                    "confirmation_status": "confirmed",
+                    "err": { "Ok": null },
+                }],
+            }),
+        ),
+    ])
+    .await;
+    let (mut miner, _) = fake_miner(url, &Keypair::new());
+    miner.bundle_transport = BundleTransport::Http;
+    let mut uuids = vec!["landed".to_string(), "failed".to_string()];
+    uuids.extend((0..5).map(|i| format!("unknown-{}", i)));
+
+    let statuses = miner.get_bundle_statuses(&uuids).await.unwrap();
+    assert_eq!(
+        statuses[..3],
+        [
+            BundleStatus::Landed {
+                slot: 91,
+                signatures: vec!["signature".to_string()]
+            },
+            BundleStatus::Failed,
+            BundleStatus::Pending,
+        ]
+    );
+    assert!(statuses[3..].iter().all(|status| status.eq(&BundleStatus::Pending)));
+
+    // Seven bundles take two inflight requests, and only landed ones are looked up again
+    let requests = api.requests();
+    let inflight: Vec<usize> = requests
+        .iter()
+        .filter(|request| request["method"].eq("getInflightBundleStatuses"))
+        .map(|request| request["params"][0].as_array().unwrap().len())
+        .collect();
+    assert_eq!(inflight, vec![MAX_BUNDLE_STATUS_IDS, 2]);
+    let landed: Vec<_> = requests
+        .iter()
+        .filter(|request| request["method"].eq("getBundleStatuses"))
+        .map(|request| request["params"][0].clone())
+        .collect();
+    assert!(!landed.is_empty());
+    assert!(landed.iter().all(|ids| ids.eq(&json!(["landed"]))));
+}
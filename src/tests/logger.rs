@@ -0,0 +1,53 @@
+use std::fs;
+
+use tempfile::TempDir;
+
+use crate::logger::{is_own_record, RotatingFile};
+
+// Bytes each test line takes in the file, with its newline
+const LINE_SIZE: u64 = 5;
+
+#[test]
+fn tells_own_records_from_the_ore_program() {
+    assert!(is_own_record("ore::mine", Some("src/mine.rs")));
+    assert!(is_own_record("ore", Some("src/main.rs")));
+
+    // The ore-program library shares the crate name, but not the sources
+    let program = "/root/.cargo/registry/src/index.crates.io/ore-program-1.2.1/src/processor/mine.rs";
+    assert!(!is_own_record("ore::processor::mine", Some(program)));
+    assert!(!is_own_record("ore::mine", None));
+    assert!(!is_own_record("ore_program", Some("src/mine.rs")));
+    assert!(!is_own_record("solana_client::rpc_client", Some("src/rpc_client.rs")));
+}
+
+#[test]
+fn shifts_rotated_files_and_drops_the_oldest() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("ore.log");
+    let mut file = RotatingFile::open(path.clone(), 2 * LINE_SIZE, 2).unwrap();
+
+    // Two lines fill a file, so every other line rotates
+    for line in ["aaaa", "bbbb", "cccc", "dddd", "eeee", "ffff", "gggg"] {
+        file.write_line(line).unwrap();
+    }
+
+    let read = |suffix: &str| fs::read_to_string(format!("{}{}", path.display(), suffix)).unwrap();
+    assert_eq!(read(""), "gggg\n");
+    assert_eq!(read(".1"), "eeee\nffff\n");
+    assert_eq!(read(".2"), "cccc\ndddd\n");
+    assert!(!dir.path().join("ore.log.3").exists());
+}
+
+#[test]
+fn truncates_in_place_without_rotated_files() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("ore.log");
+    let mut file = RotatingFile::open(path.clone(), 2 * LINE_SIZE, 0).unwrap();
+
+    for line in ["aaaa", "bbbb", "cccc"] {
+        file.write_line(line).unwrap();
+    }
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "cccc\n");
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+}
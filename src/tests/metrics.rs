@@ -0,0 +1,29 @@
+use std::{sync::Arc, time::Duration};
+
+use solana_sdk::signature::{Keypair, Signer};
+
+use super::test_miner;
+use crate::{fake_chain::FakeChain, metrics::MeteredChain};
+
+#[tokio::test]
+async fn meters_chain_calls_and_sent_bundles() {
+    let feepayer = Keypair::new();
+    let miner = test_miner(String::new(), &feepayer);
+    let metrics = miner.metrics.clone();
+    let miner = miner.with_chain(Arc::new(MeteredChain::new(FakeChain::new(), metrics.clone())));
+
+    miner.chain.get_balance(&feepayer.pubkey()).await.unwrap();
+    metrics.solution_found(feepayer.pubkey(), 1_000, Duration::from_secs(2));
+    metrics.bundles_sent(2);
+    metrics.bundle_resolved(true, 1_000);
+    metrics.bundle_resolved(false, 1_000);
+    let rendered = metrics.render();
+
+    assert!(rendered.contains(&format!("ore_hashrate{{wallet=\"{}\"}} 500", feepayer.pubkey())));
+    assert!(rendered.contains("ore_bundles_sent_total 2\n"));
+    assert!(rendered.contains("ore_bundles_landed_total 1\n"));
+    assert!(rendered.contains("ore_bundles_dropped_total 1\n"));
+    assert!(rendered.contains("ore_tips_lamports_total 1000\n"));
+    assert!(rendered.contains("ore_rpc_requests_total{method=\"getBalance\"} 1\n"));
+    assert!(rendered.contains("ore_rpc_errors_total{method=\"getBalance\"} 0\n"));
+}
@@ -0,0 +1,225 @@
+use ore::{
+    state::{Bus, Proof, Treasury},
+    utils::AccountDeserialize,
+    BUS_ADDRESSES, BUS_COUNT, INITIAL_REWARD_RATE, MINT_ADDRESS, START_AT, TOKEN_DECIMALS,
+    TREASURY_ADDRESS,
+};
+use std::sync::Arc;
+
+use clap::Parser;
+use solana_program::{
+    clock::Clock, epoch_schedule::DEFAULT_SLOTS_PER_EPOCH, keccak::Hash as KeccakHash,
+    native_token::LAMPORTS_PER_SOL, program_option::COption, program_pack::Pack, pubkey::Pubkey,
+    rent::Rent, system_program, sysvar,
+};
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+use tempfile::TempDir;
+
+use super::{program_account, test_miner};
+use crate::{
+    banks_chain::BanksChain,
+    history::{Ledger, LedgerEvent},
+    profit::LAMPORTS_PER_SIGNATURE,
+    send_and_confirm::JITO_TIP_ACCOUNT,
+    sender::SenderKind,
+    utils::{proof_pubkey, treasury_tokens_pubkey},
+    MineArgs,
+};
+
+// More wallets than fit in one transaction, so mining spans several transactions
+const WALLETS: usize = 7;
+
+// Rewards each bus starts with
+const BUS_REWARDS: u64 = 250_000_000;
+
+// Rounds mined, enough for the ledger to record the rewards of the first
+const ROUNDS: u64 = 2;
+
+fn token_program_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn system_account(lamports: u64) -> Account {
+    Account::new(lamports, 0, &system_program::ID)
+}
+
+/// Starts a bank with the Ore program initialized at the easiest difficulty and funded wallets.
+async fn start(wallets: &[Keypair]) -> (BanksClient, Keypair) {
+    let mut program_test = ProgramTest::new("ore", ore::ID, processor!(ore::process_instruction));
+
+    // Buses and treasury, as initialize would leave them
+    for (id, address) in BUS_ADDRESSES.iter().enumerate() {
+        let bus = Bus {
+            id: id as u64,
+            rewards: BUS_REWARDS,
+        };
+        program_test.add_account(*address, program_account::<Bus>(bus.to_bytes()));
+    }
+    let treasury = Treasury {
+        bump: Pubkey::find_program_address(&[ore::TREASURY], &ore::ID).1 as u64,
+        admin: Pubkey::new_unique(),
+        difficulty: KeccakHash::new_from_array([u8::MAX; 32]).into(),
+        last_reset_at: START_AT,
+        reward_rate: INITIAL_REWARD_RATE,
+        total_claimed_rewards: 0,
+    };
+    program_test.add_account(TREASURY_ADDRESS, program_account::<Treasury>(treasury.to_bytes()));
+
+    // Mint and the treasury's token account backing the rewards
+    let supply = BUS_REWARDS * BUS_COUNT as u64;
+    let mut mint = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: COption::Some(TREASURY_ADDRESS),
+        supply,
+        decimals: TOKEN_DECIMALS,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut mint);
+    program_test.add_account(MINT_ADDRESS, token_program_account(mint));
+    let mut tokens = vec![0; TokenAccount::LEN];
+    TokenAccount {
+        mint: MINT_ADDRESS,
+        owner: TREASURY_ADDRESS,
+        amount: supply,
+        delegate: COption::None,
+        state: AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+    .pack_into_slice(&mut tokens);
+    program_test.add_account(treasury_tokens_pubkey(), token_program_account(tokens));
+
+    // Mining is only open during the current epoch
+    program_test.add_sysvar_account(
+        sysvar::clock::id(),
+        &Clock {
+            slot: 0,
+            epoch_start_timestamp: 0,
+            epoch: 0,
+            leader_schedule_epoch: DEFAULT_SLOTS_PER_EPOCH,
+            unix_timestamp: START_AT + 1,
+        },
+    );
+
+    // The tip account must already be rent exempt to accept small tips
+    program_test.add_account(JITO_TIP_ACCOUNT, system_account(LAMPORTS_PER_SOL));
+    for wallet in wallets {
+        program_test.add_account(wallet.pubkey(), system_account(LAMPORTS_PER_SOL));
+    }
+
+    let (banks, feepayer, _) = program_test.start().await;
+    (banks, feepayer)
+}
+
+async fn get_proof(banks: &mut BanksClient, authority: Pubkey) -> Proof {
+    let account = banks.get_account(proof_pubkey(authority)).await.unwrap().unwrap();
+    *Proof::try_from_bytes(&account.data).unwrap()
+}
+
+async fn bus_rewards(banks: &mut BanksClient) -> u64 {
+    let mut rewards = 0;
+    for address in BUS_ADDRESSES.iter() {
+        let bus = banks.get_account(*address).await.unwrap().unwrap();
+        rewards += Bus::try_from_bytes(&bus.data).unwrap().rewards;
+    }
+    rewards
+}
+
+#[tokio::test]
+async fn mines_rounds_across_wallets() {
+    let wallets: Vec<Keypair> = (0..WALLETS).map(|_| Keypair::new()).collect();
+    let signers: Vec<&Keypair> = wallets.iter().collect();
+    let (mut banks, feepayer) = start(&wallets).await;
+    let mut miner =
+        test_miner(String::new(), &feepayer).with_chain(Arc::new(BanksChain::new(banks.clone())));
+    miner.sender = SenderKind::Rpc;
+
+    // Register every wallet, more than one transaction's worth
+    let register_ixs: Vec<_> = wallets
+        .iter()
+        .map(|wallet| ore::instruction::register(wallet.pubkey()))
+        .collect();
+    miner.send_and_confirm(&register_ixs, &signers).await.unwrap();
+
+    let ledger = TempDir::new().unwrap();
+    let ledger = ledger.path().join("history.jsonl");
+    let args = MineArgs::parse_from(["mine", "--ledger", ledger.to_str().unwrap()]);
+    let mut session = miner.start_mining(args).await;
+    let mut balances = vec![];
+    for wallet in wallets.iter() {
+        balances.push(banks.get_balance(wallet.pubkey()).await.unwrap());
+    }
+    let feepayer_balance = banks.get_balance(feepayer.pubkey()).await.unwrap();
+
+    for _ in 0..ROUNDS {
+        assert!(miner.mine_round(&mut session, &signers).await.is_continue());
+    }
+
+    // Every proof advanced each round and earned the reward rate, paid out of the buses
+    let earned = INITIAL_REWARD_RATE * ROUNDS;
+    for wallet in wallets.iter() {
+        let proof = get_proof(&mut banks, wallet.pubkey()).await;
+        assert_eq!(proof.total_hashes, ROUNDS);
+        assert_eq!(proof.claimable_rewards, earned);
+        assert_eq!(proof.total_rewards, earned);
+    }
+    let supply = BUS_REWARDS * BUS_COUNT as u64;
+    assert_eq!(bus_rewards(&mut banks).await, supply - earned * WALLETS as u64);
+
+    // The fee payer paid for every signature of the two transactions each round took,
+    // and the wallets paid nothing
+    let signatures = (2 + WALLETS as u64) * ROUNDS;
+    let spent = feepayer_balance - banks.get_balance(feepayer.pubkey()).await.unwrap();
+    assert_eq!(spent, signatures * LAMPORTS_PER_SIGNATURE);
+    for (wallet, balance) in wallets.iter().zip(balances) {
+        assert_eq!(banks.get_balance(wallet.pubkey()).await.unwrap(), balance);
+    }
+
+    // The registration and each round went out as one bundle that landed once sent
+    let metrics = miner.metrics.render();
+    assert!(metrics.contains(&format!("ore_bundles_sent_total {}\n", ROUNDS + 1)));
+    assert!(metrics.contains(&format!("ore_bundles_landed_total {}\n", ROUNDS + 1)));
+
+    // Each round is in the ledger with what its transactions cost, and the second
+    // recorded what the first earned
+    let rewards = |events: &[LedgerEvent]| -> Vec<u64> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                LedgerEvent::Reward { delta, .. } => Some(*delta),
+                _ => None,
+            })
+            .collect()
+    };
+    let events = Ledger::new(ledger.clone()).read().unwrap();
+    let fees: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            LedgerEvent::Round { fee, tip, .. } => Some(fee + tip),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(fees.len(), WALLETS * ROUNDS as usize);
+    assert_eq!(fees.iter().sum::<u64>(), spent);
+    assert_eq!(rewards(&events), vec![INITIAL_REWARD_RATE; WALLETS]);
+
+    // A restarted miner records what the last round before it earned
+    let args = MineArgs::parse_from(["mine", "--ledger", ledger.to_str().unwrap()]);
+    let mut session = miner.start_mining(args).await;
+    assert!(miner.mine_round(&mut session, &signers).await.is_continue());
+    let events = Ledger::new(ledger).read().unwrap();
+    assert_eq!(rewards(&events), vec![INITIAL_REWARD_RATE; WALLETS * ROUNDS as usize]);
+}
@@ -0,0 +1,163 @@
+//! Offline tests of the send paths and the mining pipeline, run against in-process mocks.
+
+mod block_engine;
+mod chain;
+mod claim;
+mod contention;
+mod dry_run;
+mod fund;
+mod history;
+mod jito_http;
+mod logger;
+mod metrics;
+mod mining;
+mod nonce;
+mod output;
+mod packing;
+mod payer;
+mod profit;
+mod sender;
+mod shredstream;
+mod status;
+mod sweep;
+mod tip;
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use ore::{utils::Discriminator, MINT_ADDRESS};
+use solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey, rent::Rent};
+use solana_sdk::{
+    account::Account,
+    signature::{write_keypair_file, Keypair, Signer},
+};
+use spl_token::state::{Account as TokenAccount, AccountState};
+use tempfile::TempDir;
+
+use crate::{
+    chain::Chain, fake_chain::FakeChain, send_and_confirm::BundleTransport, sender::SenderKind,
+    Miner,
+};
+
+/// A miner whose keypair files live in a temporary directory, removed when it drops.
+struct TestMiner {
+    miner: Miner,
+    _keypairs: TempDir,
+}
+
+impl TestMiner {
+    /// Replaces the chain the miner reads and writes, e.g. with a fake.
+    fn with_chain(self, chain: Arc<dyn Chain>) -> Self {
+        Self {
+            miner: self.miner.with_chain(chain),
+            _keypairs: self._keypairs,
+        }
+    }
+}
+
+impl Deref for TestMiner {
+    type Target = Miner;
+
+    fn deref(&self) -> &Miner {
+        &self.miner
+    }
+}
+
+impl DerefMut for TestMiner {
+    fn deref_mut(&mut self) -> &mut Miner {
+        &mut self.miner
+    }
+}
+
+/// Writes a keypair to a file in `dir`, returning its path.
+fn keypair_file(dir: &TempDir, keypair: &Keypair) -> String {
+    let path = dir.path().join(format!("{}.json", keypair.pubkey()));
+    write_keypair_file(keypair, &path).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+/// A miner talking to the block engine at `be_url` over gRPC with a fresh auth keypair.
+fn test_miner(be_url: String, feepayer: &Keypair) -> TestMiner {
+    let keypairs = TempDir::new().unwrap();
+    let miner = Miner::new(
+        "http://127.0.0.1:8899".to_string(),
+        be_url,
+        0,
+        Some(keypair_file(&keypairs, feepayer)),
+        Some(keypair_file(&keypairs, &Keypair::new())),
+        1000,
+        true,
+        Some(5),
+        None,
+        false,
+        false,
+        BundleTransport::Grpc,
+        SenderKind::Jito,
+        false,
+        false,
+    );
+    TestMiner {
+        miner,
+        _keypairs: keypairs,
+    }
+}
+
+/// A test miner reading and writing an in-memory fake chain, returned to set up and inspect.
+fn fake_miner(be_url: String, feepayer: &Keypair) -> (TestMiner, Arc<FakeChain>) {
+    let chain = Arc::new(FakeChain::new());
+    let miner = test_miner(be_url, feepayer).with_chain(chain.clone());
+    (miner, chain)
+}
+
+/// A rent exempt account of the Ore program holding `data` after `T`'s discriminator.
+fn program_account<T: Discriminator>(data: &[u8]) -> Account {
+    let data = [&(T::discriminator() as u64).to_le_bytes(), data].concat();
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: ore::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// An ORE token account of `owner` holding `amount` base units.
+fn ore_token_account(owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; TokenAccount::LEN];
+    TokenAccount {
+        mint: MINT_ADDRESS,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Program ids of every instruction sent to the chain so far, in order.
+fn sent_programs(chain: &FakeChain) -> Vec<Pubkey> {
+    chain
+        .sent()
+        .iter()
+        .flat_map(|tx| {
+            let keys = tx.message.static_account_keys();
+            tx.message
+                .instructions()
+                .iter()
+                .map(|ix| keys[ix.program_id_index as usize])
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
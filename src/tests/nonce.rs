@@ -0,0 +1,71 @@
+use ore::BUS_ADDRESSES;
+use solana_program::{keccak::Hash as KeccakHash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    nonce::{
+        state::{Data as NonceData, DurableNonce, Versions},
+        State as NonceState,
+    },
+    signature::{Keypair, Signer},
+};
+
+use super::fake_miner;
+use crate::{
+    nonce::nonce_pubkey, profit::LAMPORTS_PER_SIGNATURE, send_and_confirm::MAX_BUNDLE_TXS,
+};
+
+#[tokio::test]
+async fn reads_durable_nonces_through_the_chain() {
+    let feepayer = Keypair::new();
+    let (miner, chain) = fake_miner(String::new(), &feepayer);
+    let blockhashes = [Hash::new_unique(), Hash::new_unique()];
+    for (index, blockhash) in blockhashes.iter().enumerate() {
+        let data = NonceData::new(
+            feepayer.pubkey(),
+            DurableNonce::from_blockhash(blockhash),
+            LAMPORTS_PER_SIGNATURE,
+        );
+        let state = bincode::serialize(&Versions::new(NonceState::Initialized(data))).unwrap();
+        let mut account = Account::new(LAMPORTS_PER_SOL, NonceState::size(), &system_program::ID);
+        account.data[..state.len()].copy_from_slice(&state);
+        chain.set_account(nonce_pubkey(&feepayer.pubkey(), index), account);
+    }
+
+    let nonces = miner.get_durable_nonces(2).await.unwrap();
+    assert_eq!(
+        nonces.iter().map(|nonce| nonce.blockhash).collect::<Vec<_>>(),
+        blockhashes
+            .iter()
+            .map(|hash| *DurableNonce::from_blockhash(hash).as_hash())
+            .collect::<Vec<_>>()
+    );
+
+    // Nonce accounts that were never created are reported, not read as blockhashes
+    let Err(err) = miner.get_durable_nonces(3).await else {
+        panic!("expected the missing nonce account to be reported");
+    };
+    assert!(err.to_string().contains("ore nonce init --count 3"));
+}
+
+#[tokio::test]
+async fn counts_a_nonce_per_transaction_of_a_mining_round() {
+    let feepayer = Keypair::new();
+    let (mut miner, _) = fake_miner(String::new(), &feepayer);
+    miner.chunk_size = None;
+    let wallets: Vec<Keypair> = (0..40).map(|_| Keypair::new()).collect();
+    let signers: Vec<&Keypair> = wallets.iter().collect();
+
+    // Mining packs the nonce advance into every transaction, tip included
+    let count = miner.nonces_needed(&signers).await.unwrap();
+    miner.durable_nonce = true;
+    let ixs: Vec<_> = signers
+        .iter()
+        .map(|signer| {
+            ore::instruction::mine(signer.pubkey(), BUS_ADDRESSES[0], KeccakHash::default().into(), 0)
+        })
+        .collect();
+    let bundles = miner.pack_bundles(&ixs, &feepayer.pubkey(), &[], Hash::default()).unwrap();
+    assert_eq!(count, bundles.iter().map(Vec::len).sum::<usize>());
+    assert!(count > MAX_BUNDLE_TXS, "{}", count);
+}
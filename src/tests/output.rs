@@ -0,0 +1,43 @@
+use crate::{
+    busses::{BusOutput, BussesOutput},
+    output::{render_table, OreAmount, QueryOutput},
+};
+
+#[test]
+fn prints_bus_rewards_in_ore() {
+    let busses = BussesOutput {
+        busses: vec![
+            BusOutput {
+                id: 0,
+                address: "bus0".to_string(),
+                rewards: OreAmount::new(3 * 10u64.pow(ore::TOKEN_DECIMALS as u32) / 2),
+            },
+            BusOutput {
+                id: 1,
+                address: "bus1,2".to_string(),
+                rewards: OreAmount::new(123_456_789_012_345_678),
+            },
+        ],
+    };
+    assert_eq!(
+        busses.text(),
+        vec!["Bus 0: 1.5 ORE", "Bus 1: 123456789.012345678 ORE"]
+    );
+
+    let (header, rows) = busses.table();
+    assert_eq!(
+        render_table(&header, &rows),
+        "BUS  ADDRESS  REWARDS ORE\n\
+         0    bus0     1.5\n\
+         1    bus1,2   123456789.012345678\n"
+    );
+    assert_eq!(
+        busses.csv(),
+        "BUS,ADDRESS,REWARDS ORE\n0,bus0,1.5\n1,\"bus1,2\",123456789.012345678\n"
+    );
+
+    // JSON keeps amounts too large for a float exact
+    let json: serde_json::Value = serde_json::to_value(&busses).unwrap();
+    assert_eq!(json["busses"][1]["rewards"]["amount"], 123_456_789_012_345_678u64);
+    assert_eq!(json["busses"][1]["rewards"]["ui_amount"], "123456789.012345678");
+}
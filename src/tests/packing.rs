@@ -0,0 +1,135 @@
+use std::borrow::Cow;
+
+use solana_program::{
+    address_lookup_table::{
+        self,
+        state::{AddressLookupTable, LookupTableMeta},
+        AddressLookupTableAccount,
+    },
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+};
+use solana_sdk::{
+    account::Account,
+    message::{v0, VersionedMessage},
+    packet::PACKET_DATA_SIZE,
+    signature::{Keypair, Signer},
+    system_instruction::transfer,
+    transaction::VersionedTransaction,
+};
+
+use super::{fake_miner, test_miner};
+use crate::{send_and_confirm::JITO_TIP_ACCOUNT, Miner};
+
+/// Bytes of the transaction signed by the fee payer alone.
+fn signed_size(feepayer: &Keypair, ixs: &[Instruction], hash: Hash) -> usize {
+    signed_size_with(feepayer, ixs, &[], hash)
+}
+
+/// Bytes of the transaction signed by the fee payer alone, compiled against `lookup_tables`.
+fn signed_size_with(
+    feepayer: &Keypair,
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    hash: Hash,
+) -> usize {
+    let message =
+        v0::Message::try_compile(&feepayer.pubkey(), ixs, lookup_tables, hash).unwrap();
+    let tx = VersionedTransaction::try_new(VersionedMessage::V0(message), &[feepayer]).unwrap();
+    bincode::serialize(&tx).unwrap().len()
+}
+
+#[test]
+fn splits_transactions_at_the_packet_size() {
+    let feepayer = Keypair::new();
+    let mut miner = test_miner(String::new(), &feepayer);
+    miner.chunk_size = None;
+    let program = Pubkey::new_unique();
+    let ixs: Vec<_> = (0..6)
+        .map(|_| Instruction::new_with_bytes(program, &[7; 450], vec![]))
+        .collect();
+    let hash = Hash::new_unique();
+
+    let bundles = miner.pack_bundles(&ixs, &feepayer.pubkey(), &[], hash).unwrap();
+    let txs: Vec<_> = bundles.into_iter().flatten().collect();
+    assert_eq!(txs.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2, 3]);
+
+    // Every transaction fits, and none could have taken the next instruction as well
+    let tip = txs[2][2].clone();
+    assert!(tip.accounts.iter().any(|meta| meta.pubkey.eq(&JITO_TIP_ACCOUNT)));
+    for tx in txs.iter() {
+        assert!(signed_size(&feepayer, tx, hash).le(&PACKET_DATA_SIZE));
+    }
+    for tx in txs.iter().take(2) {
+        let mut grown = tx.clone();
+        grown.extend([ixs[0].clone(), tip.clone()]);
+        assert!(signed_size(&feepayer, &grown, hash).gt(&PACKET_DATA_SIZE));
+    }
+}
+
+#[test]
+fn packs_past_the_chunk_size_only_when_unlimited() {
+    let feepayer = Keypair::new();
+    let mut miner = test_miner(String::new(), &feepayer);
+    let ixs: Vec<_> = (0..12)
+        .map(|_| transfer(&feepayer.pubkey(), &Pubkey::new_unique(), 1))
+        .collect();
+    let hash = Hash::new_unique();
+
+    let txs = |miner: &Miner| -> usize {
+        let bundles = miner.pack_bundles(&ixs, &feepayer.pubkey(), &[], hash).unwrap();
+        bundles.iter().map(Vec::len).sum()
+    };
+    assert_eq!(txs(&miner), 3);
+    miner.chunk_size = None;
+    assert_eq!(txs(&miner), 1);
+}
+
+#[tokio::test]
+async fn packs_more_instructions_per_transaction_with_a_lookup_table() {
+    let feepayer = Keypair::new();
+    let (mut miner, chain) = fake_miner(String::new(), &feepayer);
+    miner.chunk_size = None;
+    let recipients: Vec<Pubkey> = (0..60).map(|_| Pubkey::new_unique()).collect();
+    let ixs: Vec<_> = recipients
+        .iter()
+        .map(|recipient| transfer(&feepayer.pubkey(), recipient, 1))
+        .collect();
+    let hash = Hash::new_unique();
+
+    // A table holding every recipient, read back through the chain like a send does
+    let table = Pubkey::new_unique();
+    let data = AddressLookupTable {
+        meta: LookupTableMeta {
+            authority: Some(feepayer.pubkey()),
+            ..LookupTableMeta::default()
+        },
+        addresses: Cow::Borrowed(&recipients),
+    }
+    .serialize_for_tests()
+    .unwrap();
+    let mut account = Account::new(1, data.len(), &address_lookup_table::program::ID);
+    account.data = data;
+    chain.set_account(table, account);
+    miner.lookup_table = Some(table);
+    let lookup_tables = miner.get_lookup_tables().await.unwrap();
+    assert_eq!(lookup_tables[0].addresses, recipients);
+
+    let txs = |lookup_tables: &[AddressLookupTableAccount]| -> Vec<Vec<Instruction>> {
+        let bundles = miner
+            .pack_bundles(&ixs, &feepayer.pubkey(), lookup_tables, hash)
+            .unwrap();
+        bundles.into_iter().flatten().collect()
+    };
+    let without_table = txs(&[]);
+    let with_table = txs(&lookup_tables);
+    assert!(with_table.len() < without_table.len());
+    assert!(with_table[0].len() > without_table[0].len());
+
+    // Transactions packed with the table only fit when compiled against it
+    for tx in with_table.iter() {
+        assert!(signed_size_with(&feepayer, tx, &lookup_tables, hash).le(&PACKET_DATA_SIZE));
+    }
+    assert!(signed_size(&feepayer, &with_table[0], hash).gt(&PACKET_DATA_SIZE));
+}
@@ -0,0 +1,58 @@
+use std::error::Error;
+
+use solana_program::{
+    instruction::InstructionError, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey,
+    system_program,
+};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signature, Signer},
+    system_instruction::transfer,
+    transaction::TransactionError,
+};
+
+use super::fake_miner;
+use crate::{
+    payer::{is_insufficient_funds, PayerMonitor},
+    sender::{FailedTransaction, SenderKind},
+};
+
+#[tokio::test]
+async fn records_payer_balance_above_minimum() {
+    let feepayer = Keypair::new();
+    let (miner, chain) = fake_miner(String::new(), &feepayer);
+    chain.set_account(
+        feepayer.pubkey(),
+        Account::new(2 * LAMPORTS_PER_SOL, 0, &system_program::ID),
+    );
+    let mut monitor = PayerMonitor::new(LAMPORTS_PER_SOL / 10, LAMPORTS_PER_SOL / 100);
+
+    let balance = miner.check_payer_balance(&mut monitor).await;
+    assert_eq!(balance, 2 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn recognizes_sends_the_fee_payer_cannot_pay_for() {
+    let feepayer = Keypair::new();
+    let (mut miner, chain) = fake_miner(String::new(), &feepayer);
+    miner.sender = SenderKind::Rpc;
+    let ix = transfer(&feepayer.pubkey(), &Pubkey::new_unique(), 1);
+
+    for err in [TransactionError::InsufficientFundsForFee, TransactionError::AccountNotFound] {
+        chain.fail_transactions(err);
+        let err = miner.send_and_confirm(&[ix.clone()], &[]).await.unwrap_err();
+        assert!(is_insufficient_funds(err.as_ref()), "{}", err);
+    }
+    chain.fail_transactions(TransactionError::BlockhashNotFound);
+    let err = miner.send_and_confirm(&[ix], &[]).await.unwrap_err();
+    assert!(!is_insufficient_funds(err.as_ref()));
+
+    // Transactions that land with the error count too, and messages alone do not
+    let landed = FailedTransaction {
+        signature: Signature::default(),
+        err: TransactionError::InstructionError(0, InstructionError::InsufficientFunds),
+    };
+    assert!(is_insufficient_funds(&landed));
+    let message: Box<dyn Error> = "insufficient funds".into();
+    assert!(!is_insufficient_funds(message.as_ref()));
+}
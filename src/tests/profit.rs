@@ -0,0 +1,50 @@
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    system_instruction::transfer,
+};
+
+use super::fake_miner;
+use crate::{
+    profit::{BundleEstimate, LAMPORTS_PER_SIGNATURE},
+    sender::SenderKind,
+};
+
+// One ORE in base units
+const ONE_ORE: u64 = 1_000_000_000;
+
+#[test]
+fn bundles_are_profitable_up_to_the_max_tip() {
+    // Two wallets earning one ORE each at 0.0001 SOL/ORE in one transaction
+    let estimate = BundleEstimate::new(2, 1, 1, ONE_ORE, 0, 0, 0.0001);
+    assert_eq!(estimate.value, 200_000);
+    assert_eq!(estimate.fees, 3 * LAMPORTS_PER_SIGNATURE);
+    let max_tip = estimate.max_tip();
+    assert_eq!(max_tip, 200_000 - 3 * LAMPORTS_PER_SIGNATURE);
+
+    assert!(BundleEstimate::new(2, 1, 1, ONE_ORE, max_tip, 0, 0.0001).is_profitable());
+    assert!(!BundleEstimate::new(2, 1, 1, ONE_ORE, max_tip + 1, 0, 0.0001).is_profitable());
+
+    // Priority fees count against the value like tips do
+    assert!(BundleEstimate::new(2, 1, 1, ONE_ORE, 0, max_tip, 0.0001).is_profitable());
+    assert!(!BundleEstimate::new(2, 1, 1, ONE_ORE, 0, max_tip + 1, 0.0001).is_profitable());
+}
+
+#[tokio::test]
+async fn estimates_the_priority_fee_rpc_sends_pay() {
+    let feepayer = Keypair::new();
+    let (mut miner, _) = fake_miner(String::new(), &feepayer);
+    miner.sender = SenderKind::Rpc;
+    miner.priority_fee = 1_000_000;
+    let ixs: Vec<_> = (0..3)
+        .map(|_| transfer(&feepayer.pubkey(), &Pubkey::new_unique(), 1))
+        .collect();
+
+    // The estimate matches what the sent transactions pay, which the jito sender does not
+    let estimate = miner.bundle_estimate(&ixs, 0, ONE_ORE, 0, 0.0001);
+    let sent = miner.send_bundles(&ixs, &[]).await.unwrap();
+    assert_eq!(estimate.fees, sent.cost.fees);
+    miner.sender = SenderKind::Jito;
+    let estimate = miner.bundle_estimate(&ixs, 0, ONE_ORE, 0, 0.0001);
+    assert!(estimate.fees < sent.cost.fees);
+}
@@ -0,0 +1,66 @@
+use solana_program::{pubkey::Pubkey, system_program};
+use solana_sdk::{
+    compute_budget::{self, ComputeBudgetInstruction},
+    signature::{Keypair, Signer},
+    system_instruction::transfer,
+    transaction::TransactionError,
+};
+
+use super::fake_miner;
+use crate::{send_and_confirm::JITO_TIP_ACCOUNT, sender::SenderKind};
+
+#[tokio::test]
+async fn sends_over_rpc_with_a_priority_fee_instead_of_a_tip() {
+    let feepayer = Keypair::new();
+    let (mut miner, chain) = fake_miner(String::new(), &feepayer);
+    miner.sender = SenderKind::Rpc;
+    miner.priority_fee = 5_000;
+
+    let ix = transfer(&feepayer.pubkey(), &Pubkey::new_unique(), 1);
+    let signatures = miner.send_and_confirm(&[ix.clone()], &[]).await.unwrap();
+
+    let sent = chain.sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(signatures, vec![sent[0].signatures[0].to_string()]);
+    let keys = sent[0].message.static_account_keys();
+    let programs: Vec<Pubkey> = sent[0]
+        .message
+        .instructions()
+        .iter()
+        .map(|ix| keys[ix.program_id_index as usize])
+        .collect();
+    assert_eq!(programs, vec![compute_budget::ID, compute_budget::ID, system_program::ID]);
+    assert!(!keys.contains(&JITO_TIP_ACCOUNT));
+    let price = &sent[0].message.instructions()[1].data;
+    assert_eq!(
+        price,
+        &ComputeBudgetInstruction::set_compute_unit_price(5_000).data
+    );
+}
+
+#[tokio::test]
+async fn keeps_what_went_out_when_a_later_bundle_fails() {
+    let feepayer = Keypair::new();
+    let (mut miner, chain) = fake_miner(String::new(), &feepayer);
+    miner.sender = SenderKind::Rpc;
+    miner.chunk_size = Some(1);
+    miner.priority_fee = 0;
+    let ixs: Vec<_> = (0..7)
+        .map(|_| transfer(&feepayer.pubkey(), &Pubkey::new_unique(), 1))
+        .collect();
+
+    // The first bundle of five goes out, and of the second only the transaction that
+    // landed before the last one failed
+    chain.fail_transactions_after(6, TransactionError::AccountInUse);
+    let err = miner.send_bundles(&ixs, &[]).await.unwrap_err();
+    let sent: Vec<String> = chain
+        .sent()
+        .iter()
+        .map(|tx| tx.signatures[0].to_string())
+        .collect();
+    assert_eq!(err.sent.ids, sent);
+    assert_eq!(err.sent.signers, vec![feepayer.pubkey()]);
+    assert_eq!(sent.len(), 6);
+    assert_eq!(err.sent.cost.fees, 6 * 5_000);
+    assert!(err.to_string().ends_with(&sent.join(", ")), "{}", err);
+}
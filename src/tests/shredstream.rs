@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+use solana_sdk::signature::Keypair;
+
+use super::fake_miner;
+
+#[tokio::test]
+async fn waits_for_the_next_shredstream_slot() {
+    let (miner, _) = fake_miner(String::new(), &Keypair::new());
+    assert_eq!(miner.wait_for_next_slot().await, None);
+
+    miner.latest_slot.send_replace(100);
+    let (next, _) = tokio::join!(miner.wait_for_next_slot(), async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        miner.latest_slot.send_replace(101);
+    });
+    assert_eq!(next, Some(101));
+
+    // A stalled stream holds the send back by at most a slot or so
+    assert_eq!(miner.wait_for_next_slot().await, None);
+}
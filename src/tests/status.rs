@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use ore::state::Proof;
+use solana_program::{
+    keccak::Hash as KeccakHash, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, system_program,
+};
+use solana_sdk::{account::Account, signature::Keypair};
+use tempfile::TempDir;
+
+use super::{history::round, program_account, test_miner};
+use crate::{
+    fake_chain::FakeChain,
+    history::Ledger,
+    metrics::MeteredChain,
+    output::{OreAmount, QueryOutput},
+    utils::proof_pubkey,
+};
+
+#[tokio::test]
+async fn fetches_every_wallet_status_in_one_request() {
+    let registered = Pubkey::new_unique();
+    let unregistered = Pubkey::new_unique();
+    let chain = FakeChain::new();
+    let proof = Proof {
+        authority: registered,
+        claimable_rewards: 500_000_000,
+        hash: KeccakHash::new_unique().into(),
+        total_hashes: 3,
+        total_rewards: 2_000_000_000,
+    };
+    chain.set_account(proof_pubkey(registered), program_account::<Proof>(proof.to_bytes()));
+    chain.set_account(
+        registered,
+        Account {
+            lamports: 2 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let dir = TempDir::new().unwrap();
+    let ledger = Ledger::new(dir.path().join("ore-history.jsonl"));
+    ledger.append(round(1_711_972_800, &registered.to_string(), "bundle"));
+    let miner = test_miner(String::new(), &Keypair::new());
+    let metrics = miner.metrics.clone();
+    let miner = miner.with_chain(Arc::new(MeteredChain::new(chain, metrics.clone())));
+
+    let status = miner.wallet_status(&[registered, unregistered], &ledger).await.unwrap();
+
+    let (first, second) = (&status.wallets[0], &status.wallets[1]);
+    assert!(first.registered);
+    assert_eq!(first.sol_balance, 2.0);
+    assert_eq!(first.ore_balance, None);
+    assert_eq!(first.claimable_rewards, Some(OreAmount::new(500_000_000)));
+    assert_eq!(first.total_rewards.as_ref().map(|r| r.ui_amount.as_str()), Some("2"));
+    assert_eq!(first.last_hash_at.as_deref(), Some("2024-04-01 12:00:00"));
+    assert!(!second.registered);
+    assert_eq!(second.total_rewards, None);
+    // A wallet without a token account shows no ORE balance rather than zero
+    assert!(status.text()[0].contains(" SOL, no ORE token account, 0.5 ORE claimable"));
+    assert!(metrics
+        .render()
+        .contains("ore_rpc_requests_total{method=\"getMultipleAccounts\"} 1\n"));
+}
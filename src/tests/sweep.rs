@@ -0,0 +1,48 @@
+use solana_program::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, rent::Rent, system_program};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+
+use super::{fake_miner, ore_token_account, sent_programs};
+use crate::sender::SenderKind;
+
+#[tokio::test]
+async fn sweeps_ore_and_sol_above_the_rent_exempt_minimum() {
+    let (mut miner, chain) = fake_miner(String::new(), &Keypair::new());
+    miner.sender = SenderKind::Rpc;
+    let rent = Rent::default().minimum_balance(0);
+    let wallets: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let signers: Vec<&Keypair> = wallets.iter().collect();
+    for (wallet, lamports) in wallets
+        .iter()
+        .zip([LAMPORTS_PER_SOL, rent, LAMPORTS_PER_SOL / 2])
+    {
+        chain.set_account(
+            wallet.pubkey(),
+            Account::new(lamports, 0, &system_program::ID),
+        );
+    }
+    let ore_account = get_associated_token_address(&wallets[0].pubkey(), &ore::MINT_ADDRESS);
+    chain.set_account(ore_account, ore_token_account(wallets[0].pubkey(), 2_000));
+    let to = Pubkey::new_unique();
+
+    // Even keeping nothing leaves every wallet rent exempt
+    let swept = miner.sweep_wallets(&signers, to, 0).await.unwrap();
+    assert_eq!(
+        swept,
+        (2_000, LAMPORTS_PER_SOL + LAMPORTS_PER_SOL / 2 - 2 * rent)
+    );
+    assert!(sent_programs(&chain).contains(&spl_associated_token_account::id()));
+
+    // Without ORE to move, the receiving token account is left alone
+    let (sent, programs) = (chain.sent().len(), sent_programs(&chain).len());
+    let swept = miner
+        .sweep_wallets(&signers[2..], to, LAMPORTS_PER_SOL / 10)
+        .await
+        .unwrap();
+    assert_eq!(swept, (0, 4 * LAMPORTS_PER_SOL / 10));
+    assert_eq!(chain.sent().len(), sent + 1);
+    assert!(!sent_programs(&chain)[programs..].contains(&spl_associated_token_account::id()));
+}
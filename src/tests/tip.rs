@@ -0,0 +1,66 @@
+use std::time::{Duration, Instant};
+
+use crate::tip::TipController;
+
+#[test]
+fn caps_the_tip_without_going_below_the_minimum() {
+    let mut controller = TipController::new(50_000, 10_000, 100_000);
+
+    assert!(controller.cap(30_000));
+    assert_eq!(controller.tip(), 30_000);
+    assert!(controller.cap(10_000));
+    assert_eq!(controller.tip(), 10_000);
+
+    // No tip in range is profitable, so the round is skipped instead of overpaying
+    assert!(!controller.cap(9_999));
+    assert_eq!(controller.tip(), 10_000);
+}
+
+#[test]
+fn raises_the_tip_after_unlanded_bundles_and_decays_it_after_landed_ones() {
+    let mut controller = TipController::new(10_000, 5_000, 20_000);
+    for uuid in ["a", "b", "c", "d"] {
+        controller.sent(uuid.to_string(), controller.tip());
+    }
+
+    // Two unlanded bundles in a row raise the tip, and a repeated result counts once
+    assert_eq!(controller.resolve("a", false), Some(10_000));
+    assert_eq!(controller.resolve("a", false), None);
+    assert_eq!(controller.tip(), 10_000);
+    assert_eq!(controller.resolve("b", false), Some(10_000));
+    assert_eq!(controller.tip(), 15_000);
+
+    // The tip never rises above the maximum
+    assert_eq!(controller.resolve("c", false), Some(10_000));
+    assert_eq!(controller.resolve("d", false), Some(10_000));
+    assert_eq!(controller.tip(), 20_000);
+    assert!(controller.pending().is_empty());
+
+    // Landed bundles decay it, down to the minimum
+    for i in 0..20 {
+        let uuid = format!("landed-{}", i);
+        controller.sent(uuid.clone(), controller.tip());
+        controller.resolve(&uuid, true);
+        assert!((5_000..=20_000).contains(&controller.tip()));
+    }
+    assert_eq!(controller.tip(), 5_000);
+}
+
+#[test]
+fn expires_bundles_without_a_result_as_unlanded() {
+    let mut controller = TipController::new(10_000, 5_000, 20_000);
+    let long_ago = Instant::now() - Duration::from_secs(60);
+    controller.sent_at("old".to_string(), 10_000, long_ago);
+    controller.sent_at("older".to_string(), 10_000, long_ago);
+    controller.sent("new".to_string(), 10_000);
+
+    let mut expired = controller.expire();
+    expired.sort();
+    assert_eq!(expired, vec!["old".to_string(), "older".to_string()]);
+    assert_eq!(controller.pending(), vec!["new".to_string()]);
+    assert_eq!(controller.tip(), 15_000);
+
+    // An expired bundle's late result no longer moves the tip
+    assert_eq!(controller.resolve("old", true), None);
+    assert_eq!(controller.tip(), 15_000);
+}
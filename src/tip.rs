@@ -0,0 +1,131 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use jito_protos::bundle::{bundle_result::Result as BundleOutcome, BundleResult};
+
+// Consecutive unlanded bundles before the tip is raised
+const RAISE_AFTER: u32 = 2;
+
+// Multiplier applied to the tip when it is raised
+const RAISE_FACTOR: f64 = 1.5;
+
+// Multiplier applied to the tip after a bundle lands
+const DECAY_FACTOR: f64 = 0.9;
+
+// Seconds without a landing result before a bundle counts as unlanded
+const LANDING_TIMEOUT: u64 = 30;
+
+/// Adjusts the Jito tip from the results of the bundles it paid for.
+pub struct TipController {
+    /// Current tip in lamports.
+    tip: u64,
+    /// Lowest tip the controller will decay to.
+    min_tip: u64,
+    /// Highest tip the controller will raise to.
+    max_tip: u64,
+    /// Number of bundles in a row that failed to land.
+    failures: u32,
+    /// Bundles awaiting a result, by uuid, with when they were sent and the tip they pay.
+    pending: HashMap<String, (Instant, u64)>,
+}
+
+impl TipController {
+    pub fn new(tip: u64, min_tip: u64, max_tip: u64) -> Self {
+        Self {
+            tip: tip.clamp(min_tip, max_tip.max(min_tip)),
+            min_tip,
+            max_tip: max_tip.max(min_tip),
+            failures: 0,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn tip(&self) -> u64 {
+        self.tip
+    }
+
+    /// Tracks a bundle sent with a tip of `tip` lamports.
+    pub fn sent(&mut self, uuid: String, tip: u64) {
+        self.sent_at(uuid, tip, Instant::now());
+    }
+
+    /// Tracks a bundle sent with a tip of `tip` lamports at `sent_at`.
+    pub fn sent_at(&mut self, uuid: String, tip: u64, sent_at: Instant) {
+        self.pending.insert(uuid, (sent_at, tip));
+    }
+
+    /// Updates the tip from a result streamed by the block engine, returning whether the
+    /// bundle landed and the tip it was sent with if this is its first final result.
+    pub fn observe(&mut self, result: &BundleResult) -> Option<(bool, u64)> {
+        let landed = match result.result {
+            Some(BundleOutcome::Processed(_)) | Some(BundleOutcome::Finalized(_)) => true,
+            Some(BundleOutcome::Rejected(_)) | Some(BundleOutcome::Dropped(_)) => false,
+            _ => return None,
+        };
+
+        self.resolve(&result.bundle_id, landed)
+            .map(|tip| (landed, tip))
+    }
+
+    /// Bundles still awaiting a result.
+    pub fn pending(&self) -> Vec<String> {
+        self.pending.keys().cloned().collect()
+    }
+
+    /// Updates the tip from the final status of a bundle, polled or streamed,
+    /// returning the tip it was sent with if this was the bundle's first final result.
+    pub fn resolve(&mut self, uuid: &str, landed: bool) -> Option<u64> {
+        // Only the first final result of a bundle counts
+        let (_, tip) = self.pending.remove(uuid)?;
+        if landed {
+            self.landed();
+        } else {
+            self.unlanded();
+        }
+        Some(tip)
+    }
+
+    /// Counts bundles without a result after the landing timeout as unlanded,
+    /// returning the uuids that expired.
+    pub fn expire(&mut self) -> Vec<String> {
+        let timeout = Duration::from_secs(LANDING_TIMEOUT);
+        let expired: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, (sent_at, _))| sent_at.elapsed().ge(&timeout))
+            .map(|(uuid, _)| uuid.clone())
+            .collect();
+        for uuid in expired.iter() {
+            self.pending.remove(uuid);
+            self.unlanded();
+        }
+        expired
+    }
+
+    /// Keeps the tip at or below the largest tip that is still profitable, returning false
+    /// and leaving the tip alone when even the minimum tip is not.
+    pub fn cap(&mut self, max_profitable_tip: u64) -> bool {
+        if max_profitable_tip.lt(&self.min_tip) {
+            return false;
+        }
+        self.tip = self.tip.min(max_profitable_tip);
+        true
+    }
+
+    fn landed(&mut self) {
+        self.failures = 0;
+        let tip = ((self.tip as f64) * DECAY_FACTOR) as u64;
+        self.tip = tip.max(self.min_tip);
+    }
+
+    fn unlanded(&mut self) {
+        self.failures += 1;
+        if self.failures.ge(&RAISE_AFTER) {
+            self.failures = 0;
+            let tip = ((self.tip as f64) * RAISE_FACTOR).ceil() as u64;
+            self.tip = tip.min(self.max_tip);
+        }
+    }
+}
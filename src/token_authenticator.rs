@@ -6,6 +6,7 @@ use jito_protos::auth::{
     auth_service_client::AuthServiceClient, GenerateAuthChallengeRequest,
     GenerateAuthTokensRequest, RefreshAccessTokenRequest, Role, Token,
 };
+use log::{debug, info, warn};
 use prost_types::Timestamp;
 use solana_metrics::datapoint_info;
 use solana_sdk::signature::{Keypair, Signer};
@@ -71,6 +72,7 @@ impl ClientInterceptor {
             })
             .await?
             .into_inner();
+        debug!(auth:% = keypair.pubkey(), role:? = role; "Authenticated with the block engine");
 
         Ok((tokens.access_token.unwrap(), tokens.refresh_token.unwrap()))
     }
@@ -109,15 +111,18 @@ impl ClientInterceptor {
                     // re-run entire auth workflow is refresh token expiring soon
                     (true, _) => {
                         let is_error = {
-                            if let Ok((new_access_token, new_refresh_token)) =
-                                Self::auth(&mut auth_service_client, &keypair, role).await
-                            {
-                                *bearer_token.write().unwrap() = new_access_token.value.clone();
-                                access_token_expiration = new_access_token.expires_at_utc.unwrap();
-                                refresh_token = new_refresh_token;
-                                false
-                            } else {
-                                true
+                            match Self::auth(&mut auth_service_client, &keypair, role).await {
+                                Ok((new_access_token, new_refresh_token)) => {
+                                    *bearer_token.write().unwrap() = new_access_token.value.clone();
+                                    access_token_expiration = new_access_token.expires_at_utc.unwrap();
+                                    refresh_token = new_refresh_token;
+                                    info!(auth:% = keypair.pubkey(); "Re-authenticated as the refresh token expires");
+                                    false
+                                }
+                                Err(err) => {
+                                    warn!(auth:% = keypair.pubkey(); "Error re-authenticating: {}", err);
+                                    true
+                                }
                             }
                         };
                         datapoint_info!("searcher-full-auth", ("is_error", is_error, bool));
@@ -125,18 +130,23 @@ impl ClientInterceptor {
                     // re-up the access token if it expires soon
                     (_, true) => {
                         let is_error = {
-                            if let Ok(refresh_resp) = auth_service_client
+                            match auth_service_client
                                 .refresh_access_token(RefreshAccessTokenRequest {
                                     refresh_token: refresh_token.value.clone(),
                                 })
                                 .await
                             {
-                                let access_token = refresh_resp.into_inner().access_token.unwrap();
-                                *bearer_token.write().unwrap() = access_token.value.clone();
-                                access_token_expiration = access_token.expires_at_utc.unwrap();
-                                false
-                            } else {
-                                true
+                                Ok(refresh_resp) => {
+                                    let access_token = refresh_resp.into_inner().access_token.unwrap();
+                                    *bearer_token.write().unwrap() = access_token.value.clone();
+                                    access_token_expiration = access_token.expires_at_utc.unwrap();
+                                    debug!(auth:% = keypair.pubkey(); "Refreshed access token");
+                                    false
+                                }
+                                Err(err) => {
+                                    warn!(auth:% = keypair.pubkey(); "Error refreshing access token: {}", err);
+                                    true
+                                }
                             }
                         };
 
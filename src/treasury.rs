@@ -1,31 +1,66 @@
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::commitment_config::CommitmentConfig;
+use serde::Serialize;
 
 use crate::{
-    utils::{get_treasury, treasury_tokens_pubkey},
+    output::{self, OreAmount, OutputFormat, QueryOutput},
+    utils::{get_token_account, get_treasury, treasury_tokens_pubkey},
     Miner,
 };
 
+/// The treasury's ORE balance and mining parameters.
+#[derive(Serialize)]
+pub struct TreasuryOutput {
+    /// ORE held by the treasury token account.
+    pub balance: OreAmount,
+    pub admin: String,
+    pub difficulty: String,
+    /// Unix timestamp of the last epoch reset.
+    pub last_reset_at: i64,
+    /// ORE paid per valid hash.
+    pub reward_rate: OreAmount,
+    pub total_claimed_rewards: OreAmount,
+}
+
+impl QueryOutput for TreasuryOutput {
+    fn text(&self) -> Vec<String> {
+        vec![
+            format!("{} ORE", self.balance),
+            format!("Admin: {}", self.admin),
+            format!("Difficulty: {}", self.difficulty),
+            format!("Last reset at: {}", self.last_reset_at),
+            format!("Reward rate: {} ORE", self.reward_rate),
+            format!("Total claimed rewards: {} ORE", self.total_claimed_rewards),
+        ]
+    }
+
+    fn table(&self) -> (Vec<&'static str>, Vec<Vec<String>>) {
+        output::fields(&[
+            ("Balance", format!("{} ORE", self.balance)),
+            ("Admin", self.admin.clone()),
+            ("Difficulty", self.difficulty.clone()),
+            ("Last reset at", self.last_reset_at.to_string()),
+            ("Reward rate", format!("{} ORE", self.reward_rate)),
+            ("Total claimed rewards", format!("{} ORE", self.total_claimed_rewards)),
+        ])
+    }
+}
+
 impl Miner {
-    pub async fn treasury(&self) {
-        let client =
-            RpcClient::new_with_commitment(self.cluster.clone(), CommitmentConfig::processed());
-        if let Ok(Some(treasury_tokens)) = client.get_token_account(&treasury_tokens_pubkey()).await
-        {
-            let treasury = get_treasury(self.cluster.clone()).await;
-            let balance = treasury_tokens.token_amount.ui_amount_string;
-            println!("{:} ORE", balance);
-            println!("Admin: {}", treasury.admin);
-            println!("Difficulty: {}", treasury.difficulty.to_string());
-            println!("Last reset at: {}", treasury.last_reset_at);
-            println!(
-                "Reward rate: {} ORE",
-                (treasury.reward_rate as f64) / 10f64.powf(ore::TOKEN_DECIMALS as f64)
-            );
-            println!(
-                "Total claimed rewards: {} ORE",
-                (treasury.total_claimed_rewards as f64) / 10f64.powf(ore::TOKEN_DECIMALS as f64)
-            );
-        }
+    pub async fn treasury(&self, format: OutputFormat) {
+        let treasury_tokens =
+            match get_token_account(self.chain.as_ref(), &treasury_tokens_pubkey()).await {
+                Ok(Some(treasury_tokens)) => treasury_tokens,
+                Ok(None) => output::fail("Treasury token account not found"),
+                Err(err) => output::fail(format!("Could not fetch the treasury tokens: {}", err)),
+            };
+        let treasury = get_treasury(self.chain.as_ref()).await;
+        let treasury = TreasuryOutput {
+            balance: OreAmount::new(treasury_tokens.amount),
+            admin: treasury.admin.to_string(),
+            difficulty: treasury.difficulty.to_string(),
+            last_reset_at: treasury.last_reset_at,
+            reward_rate: OreAmount::new(treasury.reward_rate),
+            total_claimed_rewards: OreAmount::new(treasury.total_claimed_rewards),
+        };
+        output::print(&treasury, format);
     }
 }
@@ -5,7 +5,7 @@ use crate::Miner;
 
 impl Miner {
     pub async fn update_difficulty(&self) {
-        let signer = self.signer();
+        let signer = self.feepayer();
         // let new_difficulty = KeccakHash::new_from_array([
         //     0, 0, 0, 64, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255,
         //     255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255,
@@ -17,7 +17,7 @@ impl Miner {
         let ix = ore::instruction::update_difficulty(signer.pubkey(), new_difficulty.into());
         // let bs58data = bs58::encode(ix.data).into_string();
         // println!("Data: {:?}", bs58data);
-        self.send_and_confirm(&[ix], false,false)
+        self.send_and_confirm(&[ix], &[])
             .await
             .expect("Transaction failed");
     }
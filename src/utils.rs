@@ -5,39 +5,82 @@ use ore::{
     utils::AccountDeserialize,
     MINT_ADDRESS, PROOF, TREASURY_ADDRESS,
 };
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_program::{pubkey::Pubkey, sysvar};
-use solana_sdk::{clock::Clock, commitment_config::CommitmentConfig};
+use solana_client::client_error::{ClientErrorKind, Result as ClientResult};
+use solana_program::{program_pack::Pack, pubkey::Pubkey, sysvar};
+use solana_sdk::{account::Account, clock::Clock};
 use spl_associated_token_account::get_associated_token_address;
+use spl_token::{amount_to_ui_amount_string_trimmed, state::Account as TokenAccount};
 
-pub async fn get_treasury(cluster: String) -> Treasury {
-    let client = RpcClient::new_with_commitment(cluster, CommitmentConfig::processed());
-    let data = client
-        .get_account_data(&TREASURY_ADDRESS)
+use crate::chain::Chain;
+
+// getMultipleAccounts accepts at most 100 addresses per request
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+pub async fn get_treasury(chain: &dyn Chain) -> Treasury {
+    let data = get_account_data(chain, &TREASURY_ADDRESS)
         .await
         .expect("Failed to get treasury account");
     *Treasury::try_from_bytes(&data).expect("Failed to parse treasury account")
 }
 
-pub async fn get_proof(cluster: String, authority: Pubkey) -> Proof {
-    let client = RpcClient::new_with_commitment(cluster, CommitmentConfig::processed());
+pub async fn get_proof(chain: &dyn Chain, authority: Pubkey) -> Proof {
     let proof_address = proof_pubkey(authority);
-    let data = client
-        .get_account_data(&proof_address)
+    let data = get_account_data(chain, &proof_address)
         .await
         .expect("Failed to get miner account");
     *Proof::try_from_bytes(&data).expect("Failed to parse miner account")
 }
 
-pub async fn get_clock_account(cluster: String) -> Clock {
-    let client = RpcClient::new_with_commitment(cluster, CommitmentConfig::processed());
-    let data = client
-        .get_account_data(&sysvar::clock::ID)
+pub async fn get_clock_account(chain: &dyn Chain) -> Clock {
+    let data = get_account_data(chain, &sysvar::clock::ID)
         .await
         .expect("Failed to get miner account");
     bincode::deserialize::<Clock>(&data).expect("Failed to deserialize clock")
 }
 
+/// The data of the account at `address`, an error if it does not exist.
+pub async fn get_account_data(chain: &dyn Chain, address: &Pubkey) -> ClientResult<Vec<u8>> {
+    match chain.get_account(address).await? {
+        Some(account) => Ok(account.data),
+        None => Err(ClientErrorKind::Custom(format!("AccountNotFound: pubkey={}", address)).into()),
+    }
+}
+
+/// The accounts at `addresses`, fetched in as few requests as the RPC node allows.
+pub async fn get_multiple_accounts_batched(
+    chain: &dyn Chain,
+    addresses: &[Pubkey],
+) -> ClientResult<Vec<Option<Account>>> {
+    let mut accounts = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        accounts.extend(chain.get_multiple_accounts(chunk).await?);
+    }
+    Ok(accounts)
+}
+
+/// The token account at `address`, `None` if it does not exist.
+pub async fn get_token_account(
+    chain: &dyn Chain,
+    address: &Pubkey,
+) -> ClientResult<Option<TokenAccount>> {
+    match chain.get_account(address).await? {
+        Some(account) => TokenAccount::unpack(&account.data)
+            .map(Some)
+            .map_err(|err| ClientErrorKind::Custom(err.to_string()).into()),
+        None => Ok(None),
+    }
+}
+
+/// A token amount in ORE as a number, for machine-readable output.
+pub fn ore_amount(amount: u64) -> f64 {
+    (amount as f64) / 10f64.powf(ore::TOKEN_DECIMALS as f64)
+}
+
+/// A token amount in ORE, e.g. "1.5".
+pub fn ore_ui_amount(amount: u64) -> String {
+    amount_to_ui_amount_string_trimmed(amount, ore::TOKEN_DECIMALS)
+}
+
 #[cached]
 pub fn proof_pubkey(authority: Pubkey) -> Pubkey {
     Pubkey::find_program_address(&[PROOF, authority.as_ref()], &ore::ID).0